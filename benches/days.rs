@@ -0,0 +1,193 @@
+//! Times parsing and both parts of every day, and the edit search of
+//! `mutate` on the day 8 program, on the example inputs in
+//! `benches/examples` and on the real inputs when present.
+//!
+//! ```text
+//! cargo bench [-- [<filter>] [--save-baseline <name>] [--baseline <name>]]
+//! ```
+//!
+//! Only benchmarks whose id (e.g. `day07/input/part2`) contains the filter
+//! are run. Medians are compared against the `base` baseline, which is then
+//! updated. `--save-baseline` compares against and updates another one, and
+//! `--baseline` only compares. Baselines are kept in `target/aoc-bench`.
+
+use advent_of_code2019::bench::{self, Baseline, Change, Config};
+use advent_of_code2019::input::Inputs;
+use advent_of_code2019::intcode::{Termination, CPU};
+use advent_of_code2019::mutate::{self, FlipJmpNop};
+use advent_of_code2019::solution::{self, Answer, Day};
+use std::env;
+use std::hint::black_box;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::process;
+
+struct Options {
+    filter: Option<String>,
+    compare: String,
+    save: Option<String>,
+}
+
+fn parse_args() -> Options {
+    let mut options = Options {
+        filter: None,
+        compare: "base".to_string(),
+        save: Some("base".to_string()),
+    };
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--save-baseline" | "--baseline" => {
+                let name = args.next().unwrap_or_else(|| {
+                    eprintln!("{} needs a name", arg);
+                    process::exit(2);
+                });
+                options.save = if arg == "--baseline" {
+                    None
+                } else {
+                    Some(name.clone())
+                };
+                options.compare = name;
+            }
+            // Passed by cargo bench
+            "--bench" => {}
+            s if s.starts_with("--") => {
+                eprintln!("unknown option {}", s);
+                process::exit(2);
+            }
+            s => options.filter = Some(s.to_string()),
+        }
+    }
+    options
+}
+
+fn baseline_path(name: &str) -> PathBuf {
+    env::var_os("CARGO_TARGET_DIR")
+        .map_or_else(|| PathBuf::from("target"), PathBuf::from)
+        .join("aoc-bench")
+        .join(format!("{}.txt", name))
+}
+
+struct Bencher<'a> {
+    config: Config,
+    filter: Option<&'a str>,
+    old: Baseline,
+    new: Baseline,
+}
+
+impl Bencher<'_> {
+    fn wanted(&self, id: &str) -> bool {
+        self.filter.is_none_or(|f| id.contains(f))
+    }
+
+    fn run<F: FnMut()>(&mut self, id: &str, routine: F) {
+        let stats = bench::measure(&self.config, routine);
+        let change = match self.old.get(id) {
+            Some(old) => Change::new(old, stats.median()).to_string(),
+            None => "no baseline".to_string(),
+        };
+        println!(
+            "{:<24} time: [{} {} {}]  {}",
+            id,
+            bench::format_ns(stats.min()),
+            bench::format_ns(stats.median()),
+            bench::format_ns(stats.max()),
+            change
+        );
+        self.new.insert(id, stats.median());
+    }
+
+    /// Benchmarks the parse and both parts of `day` on one input. A phase
+    /// that fails or panics when tried once is skipped.
+    fn day(&mut self, day: &Day, kind: &str, input: &str) {
+        let prefix = format!("day{:02}/{}", day.day, kind);
+        let phases = ["parse", "part1", "part2"];
+        if !phases
+            .iter()
+            .any(|phase| self.wanted(&format!("{}/{}", prefix, phase)))
+        {
+            return;
+        }
+        let parsed = match day.parse(input) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                println!("{:<24} skipped: {}", format!("{}/parse", prefix), e);
+                return;
+            }
+        };
+        let id = format!("{}/parse", prefix);
+        if self.wanted(&id) {
+            self.run(&id, || drop(black_box(day.parse(black_box(input)))));
+        }
+
+        for part in 1..=2 {
+            let id = format!("{}/part{}", prefix, part);
+            if !self.wanted(&id) {
+                continue;
+            }
+            match panic::catch_unwind(AssertUnwindSafe(|| day.solve(part, &*parsed))) {
+                Ok(Ok(Answer::None)) => {}
+                Ok(Ok(_)) => self.run(&id, || drop(black_box(day.solve(part, &*parsed)))),
+                Ok(Err(e)) => println!("{:<24} skipped: {}", id, e),
+                Err(_) => println!("{:<24} skipped: panicked", id),
+            }
+        }
+    }
+}
+
+fn main() {
+    let options = parse_args();
+    // Failing phases are reported as skipped
+    panic::set_hook(Box::new(|_| {}));
+
+    let old = Baseline::load(&baseline_path(&options.compare)).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+    let mut bencher = Bencher {
+        config: Config::default(),
+        filter: options.filter.as_deref(),
+        old,
+        new: Baseline::default(),
+    };
+
+    let sources = [
+        ("example", Inputs::new("benches/examples")),
+        ("input", Inputs::from_env()),
+    ];
+    for day in solution::registry() {
+        for (kind, inputs) in &sources {
+            if let Ok(input) = inputs.load(day.day) {
+                bencher.day(&day, kind, &input);
+            }
+        }
+    }
+    for (kind, inputs) in &sources {
+        let id = format!("mutate/{}", kind);
+        let cpu = inputs.load(8).ok().and_then(|input| CPU::new(&input).ok());
+        if let (true, Some(cpu)) = (bencher.wanted(&id), cpu) {
+            let halts = |t: &Termination, _: &CPU| matches!(t, Termination::Halted { .. });
+            let config = mutate::Config::default();
+            bencher.run(&id, || {
+                let code = black_box(&cpu.code);
+                drop(black_box(mutate::search(
+                    code,
+                    &[&FlipJmpNop],
+                    halts,
+                    &config,
+                )))
+            });
+        }
+    }
+
+    if let Some(name) = options.save {
+        // Keep the entries of benchmarks that were filtered out
+        let path = baseline_path(&name);
+        let mut baseline = Baseline::load(&path).unwrap_or_default();
+        baseline.extend(&bencher.new);
+        if let Err(e) = baseline.save(&path) {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    }
+}
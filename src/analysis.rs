@@ -0,0 +1,163 @@
+//! Static analysis of handheld console programs. A program's control-flow
+//! graph has one edge out of every instruction, so which instructions lead
+//! to the end can be worked out without running it.
+
+use crate::intcode::{Op, Termination, CPU};
+use std::convert::TryFrom;
+
+/// Where execution goes after `op` at `pc`, if anywhere: `code.len()` is
+/// the end of the program, and jumps out of it go nowhere. Intcode
+/// instructions go nowhere either.
+pub fn successor(code: &[Op], pc: usize, op: Op) -> Option<usize> {
+    let offset = match op {
+        Op::Nop(_) | Op::Acc(_) => 1,
+        Op::Jmp(offset) => offset,
+        _ => return None,
+    };
+    let next = i64::try_from(pc).ok()?.checked_add(offset)?;
+    usize::try_from(next)
+        .ok()
+        .filter(|next| *next <= code.len())
+}
+
+/// The control-flow graph of a program, with `code.len()` as the node for
+/// its end.
+#[derive(Clone, Debug)]
+pub struct Cfg {
+    successors: Vec<Option<usize>>,
+    predecessors: Vec<Vec<usize>>,
+}
+
+impl Cfg {
+    pub fn new(code: &[Op]) -> Self {
+        let successors = code
+            .iter()
+            .enumerate()
+            .map(|(pc, op)| successor(code, pc, *op))
+            .collect::<Vec<_>>();
+        let mut predecessors = vec![Vec::new(); code.len() + 1];
+        for (pc, next) in successors.iter().enumerate() {
+            if let Some(next) = next {
+                predecessors[*next].push(pc);
+            }
+        }
+        Cfg {
+            successors,
+            predecessors,
+        }
+    }
+
+    pub fn successor(&self, pc: usize) -> Option<usize> {
+        self.successors.get(pc).copied().flatten()
+    }
+
+    /// For every node, whether running from it ends the program.
+    pub fn reaches_end(&self) -> Vec<bool> {
+        let end = self.successors.len();
+        let mut reaches = vec![false; end + 1];
+        reaches[end] = true;
+        let mut stack = vec![end];
+        while let Some(pc) = stack.pop() {
+            for &prev in &self.predecessors[pc] {
+                if !reaches[prev] {
+                    reaches[prev] = true;
+                    stack.push(prev);
+                }
+            }
+        }
+        reaches
+    }
+
+    /// The instructions run from the start, in order, until the program
+    /// ends, leaves the graph or gets back to one of them.
+    pub fn path(&self) -> Vec<usize> {
+        let mut seen = vec![false; self.successors.len()];
+        let mut path = Vec::new();
+        let mut pc = Some(0);
+        while let Some(p) = pc.filter(|p| *p < seen.len() && !seen[*p]) {
+            seen[p] = true;
+            path.push(p);
+            pc = self.successor(p);
+        }
+        path
+    }
+}
+
+/// A changed instruction and the accumulator the program ends with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Patch {
+    pub pc: usize,
+    pub op: Op,
+    pub acc: i64,
+}
+
+/// Finds the `jmp` to turn into a `nop`, or the other way around, that makes
+/// the program end, in time linear in its length.
+///
+/// Only an instruction on the path the program takes can matter. Flipping
+/// one there ends the program if and only if its new successor reaches the
+/// end unchanged: a way back to the flipped instruction would also be a way
+/// to the end for the unpatched program, which doesn't end. So at most one
+/// pass over the path is needed.
+pub fn terminating_flip(code: &[Op]) -> Option<Patch> {
+    let cfg = Cfg::new(code);
+    let reaches_end = cfg.reaches_end();
+    if reaches_end[0] {
+        // Ends already, there is nothing to fix
+        return None;
+    }
+    let (pc, op) = cfg.path().into_iter().find_map(|pc| {
+        let flipped = match code[pc] {
+            Op::Jmp(v) => Op::Nop(v),
+            Op::Nop(v) => Op::Jmp(v),
+            _ => return None,
+        };
+        successor(code, pc, flipped)
+            .filter(|next| reaches_end[*next])
+            .map(|_| (pc, flipped))
+    })?;
+
+    let mut cpu = CPU::from_code(code.to_vec());
+    cpu.code[pc] = op;
+    match cpu.run() {
+        Termination::Halted { acc } => Some(Patch { pc, op, acc }),
+        // Can only be an overflow of acc
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intcode::{Op, EXAMPLE};
+
+    #[test]
+    fn analysis_cfg() {
+        let code = crate::asm::assemble(EXAMPLE).unwrap();
+        let cfg = super::Cfg::new(&code);
+        assert_eq!(cfg.path(), vec![0, 1, 2, 6, 7, 3, 4]);
+        let reaches = cfg.reaches_end();
+        let ending = (0..=code.len()).filter(|pc| reaches[*pc]);
+        assert_eq!(ending.collect::<Vec<_>>(), vec![8, 9]);
+        // Out of the program
+        assert_eq!(super::successor(&code, 0, Op::Jmp(-1)), None);
+        assert_eq!(super::successor(&code, 0, Op::Jmp(9)), Some(9));
+    }
+
+    #[test]
+    fn analysis_terminating_flip() {
+        let code = crate::asm::assemble(EXAMPLE).unwrap();
+        assert_eq!(
+            super::terminating_flip(&code),
+            Some(super::Patch {
+                pc: 7,
+                op: Op::Nop(-4),
+                acc: 8
+            })
+        );
+        // No flip helps, or nothing to fix
+        let code = crate::asm::assemble("jmp +0\njmp -1").unwrap();
+        assert_eq!(super::terminating_flip(&code), None);
+        let code = crate::asm::assemble("jmp +1").unwrap();
+        assert_eq!(super::terminating_flip(&code), None);
+    }
+}
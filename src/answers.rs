@@ -0,0 +1,120 @@
+//! Known answers for the real inputs, kept in a small TOML subset:
+//!
+//! ```text
+//! [day1]
+//! part1 = 1007104
+//! part2 = "some,text"
+//! ```
+
+use crate::error::{Error, Result};
+use crate::solution::Answer;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Default)]
+pub struct Answers {
+    answers: BTreeMap<(usize, usize), Answer>,
+}
+
+fn parse_value(value: &str) -> Option<Answer> {
+    if let Some(s) = value.strip_prefix('"') {
+        return s.strip_suffix('"').map(|s| Answer::Str(s.to_string()));
+    }
+    value.parse().ok().map(Answer::Int)
+}
+
+impl Answers {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut answers = BTreeMap::new();
+        let mut day = None;
+
+        for (idx, line) in text.lines().enumerate() {
+            let invalid = || Error::Answers {
+                line: idx + 1,
+                text: line.to_string(),
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(section) = line.strip_prefix('[') {
+                let d = section
+                    .strip_suffix(']')
+                    .and_then(|s| s.strip_prefix("day"))
+                    .and_then(|s| s.parse::<usize>().ok())
+                    .ok_or_else(invalid)?;
+                day = Some(d);
+                continue;
+            }
+
+            let (key, value) = line.split_once('=').ok_or_else(invalid)?;
+            let part = match key.trim() {
+                "part1" => 1,
+                "part2" => 2,
+                _ => return Err(invalid()),
+            };
+            let day = day.ok_or_else(invalid)?;
+            let value = parse_value(value.trim()).ok_or_else(invalid)?;
+            answers.insert((day, part), value);
+        }
+
+        Ok(Answers { answers })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).map_err(|source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&text)
+    }
+
+    pub fn get(&self, day: usize, part: usize) -> Option<&Answer> {
+        self.answers.get(&(day, part))
+    }
+
+    /// The days with at least one known answer, in order.
+    pub fn days(&self) -> Vec<usize> {
+        let mut days = self.answers.keys().map(|(d, _)| *d).collect::<Vec<_>>();
+        days.dedup();
+        days
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::solution::Answer;
+
+    #[test]
+    fn answers_parse() {
+        let answers = super::Answers::parse(
+            "# comment
+[day1]
+part1 = 514579
+part2 = 241861950
+
+[day21]
+part2 = \"mxmxvkd,sqjhc,fvjkl\"
+",
+        )
+        .unwrap();
+        assert_eq!(answers.get(1, 1), Some(&Answer::Int(514579)));
+        assert_eq!(answers.get(1, 2), Some(&Answer::Int(241861950)));
+        assert_eq!(
+            answers.get(21, 2),
+            Some(&Answer::Str("mxmxvkd,sqjhc,fvjkl".to_string()))
+        );
+        assert_eq!(answers.get(21, 1), None);
+        assert_eq!(answers.days(), vec![1, 21]);
+    }
+
+    #[test]
+    fn answers_parse_error() {
+        let err = super::Answers::parse("[day1]\npart3 = 1").unwrap_err();
+        assert!(matches!(err, crate::error::Error::Answers { line: 2, .. }));
+        assert!(super::Answers::parse("part1 = 1").is_err());
+        assert!(super::Answers::parse("[day1]\npart1 = \"abc").is_err());
+    }
+}
@@ -1,15 +1,35 @@
 use super::helper::*;
+use crate::error::{Error, Result};
+use crate::solution::{Answer, Solution};
 use nom::character::complete::newline;
 use nom::multi::separated_list1;
 use nom::*;
 use std::collections::HashSet;
-use std::fs;
 
-pub fn run() {
-    let input = fs::read_to_string("day1.txt").unwrap();
-    //47424
-    println!("1:1: {}", run_1(&input));
-    println!("1:2: {}", run_2(&input));
+pub struct Day1;
+
+impl Solution for Day1 {
+    const DAY: usize = 1;
+    type Input = HashSet<usize>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let input = parse_day(Self::DAY, input, parse_input)?;
+        Ok(input.into_iter().collect())
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer> {
+        run_1(input).map(Answer::from).ok_or(Error::NoSolution {
+            day: Self::DAY,
+            part: 1,
+        })
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer> {
+        run_2(input).map(Answer::from).ok_or(Error::NoSolution {
+            day: Self::DAY,
+            part: 2,
+        })
+    }
 }
 
 fn parse_input(i: &str) -> IResult<&str, Vec<usize>> {
@@ -24,23 +44,21 @@ fn solve(inputs: &HashSet<usize>, target: usize) -> Option<usize> {
         .map(|&val| val * (target - val))
 }
 
-fn run_1(input: &str) -> usize {
-    let (_, input) = parse_input(input).unwrap();
-    let input: std::collections::HashSet<usize> = input.into_iter().collect();
-    solve(&input, 2020).unwrap()
+fn run_1(input: &HashSet<usize>) -> Option<usize> {
+    solve(input, 2020)
 }
 
-fn run_2(input: &str) -> usize {
-    let (_, input) = parse_input(input).unwrap();
-    let input: std::collections::HashSet<usize> = input.into_iter().collect();
+fn run_2(input: &HashSet<usize>) -> Option<usize> {
     input
         .iter()
-        .find_map(|v1| solve(&input, 2020 - v1).map(|v2| v1 * v2))
-        .unwrap()
+        .filter(|&&v1| v1 <= 2020)
+        .find_map(|v1| solve(input, 2020 - v1).map(|v2| v1 * v2))
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::solution::Solution;
+
     const INPUT: &str = "1721
 979
 366
@@ -50,11 +68,13 @@ mod tests {
 
     #[test]
     fn aoc1_run_1() {
-        assert_eq!(super::run_1(INPUT), 514579);
+        let input = super::Day1::parse(INPUT).unwrap();
+        assert_eq!(super::run_1(&input), Some(514579));
     }
 
     #[test]
     fn aoc1_run_2() {
-        assert_eq!(super::run_2(INPUT), 241861950);
+        let input = super::Day1::parse(INPUT).unwrap();
+        assert_eq!(super::run_2(&input), Some(241861950));
     }
 }
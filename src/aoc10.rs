@@ -1,16 +1,31 @@
+use crate::error::Result;
+use crate::helper::parse_day;
+use crate::solution::{Answer, Solution};
 use std::collections::HashMap;
-use std::fs;
 
-pub fn run() {
-    let input = fs::read_to_string("day10.txt").unwrap();
-    println!("10:1 {}", run_1(&input));
-    println!("10:2 {}", run_2(&input));
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: usize = 10;
+    type Input = Vec<usize>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_day(Self::DAY, input, parse)
+    }
+
+    fn part1(adapters: &Self::Input) -> Result<Answer> {
+        Ok(run_1(adapters).into())
+    }
+
+    fn part2(adapters: &Self::Input) -> Result<Answer> {
+        Ok(run_2(adapters).into())
+    }
 }
 
-fn run_1(input: &str) -> usize {
-    let (_, mut input) = parse(input).unwrap();
+fn run_1(adapters: &[usize]) -> usize {
+    let mut input = adapters.to_vec();
     input.push(0);
-    input.sort();
+    input.sort_unstable();
     let built_in = input.iter().max().unwrap() + 3;
     input.push(built_in);
 
@@ -55,10 +70,10 @@ fn combinations(v: usize, vals: &[usize], cache: &mut HashMap<usize, u128>) -> u
     combs
 }
 
-fn run_2(input: &str) -> u128 {
-    let (_, mut input) = parse(input).unwrap();
+fn run_2(adapters: &[usize]) -> u128 {
+    let mut input = adapters.to_vec();
     input.push(0);
-    input.sort();
+    input.sort_unstable();
     let built_in = input.iter().max().unwrap() + 3;
     let input = input.into_iter().rev().collect::<Vec<_>>();
     let mut cache = HashMap::new();
@@ -74,6 +89,8 @@ fn parse(i: &str) -> nom::IResult<&str, Vec<usize>> {
 
 #[cfg(test)]
 mod tests {
+    use crate::solution::Solution;
+
     const INPUT_1: &str = "16
 10
 15
@@ -133,13 +150,13 @@ mod tests {
 
     #[test]
     fn aoc10_run_1() {
-        assert_eq!(super::run_1(INPUT_1), 7 * 5);
-        assert_eq!(super::run_1(INPUT_2), 220);
+        assert_eq!(super::run_1(&super::Day10::parse(INPUT_1).unwrap()), 7 * 5);
+        assert_eq!(super::run_1(&super::Day10::parse(INPUT_2).unwrap()), 220);
     }
 
     #[test]
     fn aoc10_run_2() {
-        assert_eq!(super::run_2(INPUT_1), 8);
-        assert_eq!(super::run_2(INPUT_2), 19208);
+        assert_eq!(super::run_2(&super::Day10::parse(INPUT_1).unwrap()), 8);
+        assert_eq!(super::run_2(&super::Day10::parse(INPUT_2).unwrap()), 19208);
     }
 }
@@ -1,167 +1,107 @@
-use std::collections::HashMap;
-use std::fs;
+use crate::automaton::{Automaton, Bounded, Rule};
+use crate::error::Result;
+use crate::helper::{Grid, Pos, NEIGHBOURS8};
+use crate::solution::{Answer, Solution};
+use std::fmt;
 
-pub fn run() {
-    let input = fs::read_to_string("day11.txt").unwrap();
-    println!("11:1: {}", run_1(&input));
-    println!("11:2: {}", run_2(&input));
-}
-
-type Coord = (isize, isize);
+pub struct Day11;
 
-#[derive(Clone)]
-struct Grid {
-    seats: HashMap<Coord, bool>,
-    width: isize,
-    height: isize,
-}
+impl Solution for Day11 {
+    const DAY: usize = 11;
+    type Input = Grid<Seat>;
 
-impl Grid {
-    fn new(input: &str) -> Self {
-        let mut height = 0;
-        let mut width = 0;
-        let mut seats = HashMap::new();
-        for (y, line) in input.lines().enumerate() {
-            height = y + 1;
-
-            for (x, c) in line.chars().enumerate() {
-                width = width.max(x + 1);
-                match c {
-                    'L' => {
-                        seats.insert((x as isize, y as isize), false);
-                    }
-                    '#' => {
-                        seats.insert((x as isize, y as isize), true);
-                    }
-                    _ => (),
-                }
-            }
-        }
-        Grid {
-            seats,
-            height: height as isize,
-            width: width as isize,
-        }
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input).map_err(|e| e.for_day(Self::DAY))
     }
-    fn num_occupied(&self, (x, y): Coord) -> usize {
-        let nbrs = [
-            (x - 1, y),
-            (x - 1, y - 1),
-            (x, y - 1),
-            (x + 1, y - 1),
-            (x + 1, y),
-            (x + 1, y + 1),
-            (x, y + 1),
-            (x - 1, y + 1),
-        ];
-        nbrs.iter()
-            .filter(|c| *self.seats.get(c).unwrap_or(&false))
-            .count()
+
+    fn part1(grid: &Self::Input) -> Result<Answer> {
+        Ok(run_1(grid).into())
     }
-    fn num_occupied_visible(&self, (x, y): Coord) -> usize {
-        let dirs = [
-            (-1, 0),
-            (-1, -1),
-            (0, -1),
-            (1, -1),
-            (1, 0),
-            (1, 1),
-            (0, 1),
-            (-1, 1),
-        ];
-        let mut num_visible = 0;
-        for dir in &dirs {
-            for i in 1..1000 {
-                let pos = (x + dir.0 * i, y + dir.1 * i);
-                if pos.0 >= 0 && pos.1 >= 0 && pos.0 < self.width && pos.1 < self.height {
-                    match self.seats.get(&pos) {
-                        Some(true) => {
-                            num_visible += 1;
-                            break;
-                        }
-                        // Empty chair blocks the view, so stop looking
-                        Some(false) => break,
-                        None => (),
-                    }
-                } else {
-                    break;
-                }
-            }
-        }
-        num_visible
+
+    fn part2(grid: &Self::Input) -> Result<Answer> {
+        Ok(run_2(grid).into())
     }
 }
 
-fn run_1(input: &str) -> usize {
-    let grid = Grid::new(input);
-    let mut grid_1 = grid.clone();
-    let mut grid_2 = grid;
-    let mut cnt = 0;
-    loop {
-        let (cur, next) = if cnt % 2 == 0 {
-            (&grid_1, &mut grid_2)
-        } else {
-            (&grid_2, &mut grid_1)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Seat {
+    Floor,
+    Empty,
+    Occupied,
+}
+
+impl fmt::Display for Seat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let c = match self {
+            Seat::Floor => '.',
+            Seat::Empty => 'L',
+            Seat::Occupied => '#',
         };
-        cnt += 1;
-
-        for (c, v) in cur.seats.iter() {
-            match (v, cur.num_occupied(*c)) {
-                (false, 0) => {
-                    next.seats.insert(*c, true);
-                }
-                (true, o) if o > 3 => {
-                    next.seats.insert(*c, false);
-                }
-                _ => {
-                    next.seats.insert(*c, *v);
-                }
-            }
-        }
-        if cur.seats == next.seats {
-            break;
-        }
+        write!(f, "{}", c)
     }
+}
 
-    grid_1.seats.iter().filter(|(_, taken)| **taken).count()
+fn parse(input: &str) -> Result<Grid<Seat>> {
+    Grid::parse(input, |c| match c {
+        '.' => Some(Seat::Floor),
+        'L' => Some(Seat::Empty),
+        '#' => Some(Seat::Occupied),
+        _ => None,
+    })
 }
 
-fn run_2(input: &str) -> usize {
-    let grid = Grid::new(input);
-    let mut grid_1 = grid.clone();
-    let mut grid_2 = grid;
-    let mut cnt = 0;
-    loop {
-        let (cur, next) = if cnt % 2 == 0 {
-            (&grid_1, &mut grid_2)
-        } else {
-            (&grid_2, &mut grid_1)
-        };
-        cnt += 1;
-
-        for (c, v) in cur.seats.iter() {
-            match (v, cur.num_occupied_visible(*c)) {
-                (false, 0) => {
-                    next.seats.insert(*c, true);
-                }
-                (true, o) if o > 4 => {
-                    next.seats.insert(*c, false);
-                }
-                _ => {
-                    next.seats.insert(*c, *v);
-                }
-            }
-        }
-        if cur.seats == next.seats {
-            break;
-        }
-    }
+/// The seats next to `pos`.
+fn seats_adjacent(grid: &Grid<Seat>, pos: Pos) -> Vec<Pos> {
+    grid.neighbours8(pos)
+        .filter(|p| grid[*p] != Seat::Floor)
+        .collect()
+}
+
+/// The first seat seen from `pos` in each direction.
+fn seats_visible(grid: &Grid<Seat>, pos: Pos) -> Vec<Pos> {
+    NEIGHBOURS8
+        .iter()
+        .filter_map(|dir| {
+            grid.ray(pos, *dir)
+                .find(|(_, seat)| **seat != Seat::Floor)
+                .map(|(p, _)| p)
+        })
+        .collect()
+}
+
+/// Updates all seats at once until nothing changes, a seat being vacated
+/// when more than `tolerance` of its `neighbours` are occupied.
+fn settle(
+    grid: &Grid<Seat>,
+    neighbours: fn(&Grid<Seat>, Pos) -> Vec<Pos>,
+    tolerance: usize,
+) -> usize {
+    let seats = grid.iter().filter(|(_, s)| **s != Seat::Floor);
+    let space = Bounded::new(seats.map(|(p, _)| p), |p| neighbours(grid, *p));
+    let occupied = grid.iter().filter(|(_, s)| **s == Seat::Occupied);
+    let rule = Rule {
+        birth: 0..=0,
+        survive: 0..=tolerance,
+    };
+    let mut automaton = Automaton::new(space, rule, occupied.map(|(p, _)| p));
+    automaton.run(None);
+    automaton.live().len()
+}
 
-    grid_1.seats.iter().filter(|(_, taken)| **taken).count()
+fn run_1(grid: &Grid<Seat>) -> usize {
+    settle(grid, seats_adjacent, 3)
+}
+
+fn run_2(grid: &Grid<Seat>) -> usize {
+    settle(grid, seats_visible, 4)
 }
 
 #[cfg(test)]
 mod tests {
+    use super::Seat;
+    use crate::helper::{Grid, Pos};
+    use crate::solution::Solution;
+
     const INPUT: &str = "L.LL.LL.LL
 LLLLLLL.LL
 L.L.L..L..
@@ -175,12 +115,18 @@ L.LLLLL.LL";
 
     #[test]
     fn aoc11_run_1() {
-        assert_eq!(super::run_1(INPUT), 37)
+        let grid = super::Day11::parse(INPUT).unwrap();
+        assert_eq!(super::run_1(&grid), 37)
+    }
+
+    fn num_occupied_visible(grid: &Grid<Seat>, pos: Pos) -> usize {
+        let seats = super::seats_visible(grid, pos).into_iter();
+        seats.filter(|p| grid[*p] == Seat::Occupied).count()
     }
 
     #[test]
     fn aoc11_occupied_visible() {
-        let grid = super::Grid::new(
+        let grid = super::parse(
             ".......#.
 ...#.....
 .#.......
@@ -190,19 +136,21 @@ L.LLLLL.LL";
 .........
 #........
 ...#.....",
-        );
-        let visible = grid.seats.iter().find(|(_, v)| **v == false).unwrap();
-        assert_eq!(grid.num_occupied_visible(*visible.0), 8);
+        )
+        .unwrap();
+        let empty = grid.iter().find(|(_, s)| **s == Seat::Empty).unwrap();
+        assert_eq!(num_occupied_visible(&grid, empty.0), 8);
 
-        let grid = super::Grid::new(
+        let grid = super::parse(
             ".............
 .L.L.#.#.#.#.
 .............",
-        );
-        assert_eq!(grid.num_occupied_visible((1, 1)), 0);
-        assert_eq!(grid.num_occupied_visible((3, 1)), 1);
+        )
+        .unwrap();
+        assert_eq!(num_occupied_visible(&grid, (1, 1)), 0);
+        assert_eq!(num_occupied_visible(&grid, (3, 1)), 1);
 
-        let grid = super::Grid::new(
+        let grid = super::parse(
             ".##.##.
 #.#.#.#
 ##...##
@@ -210,12 +158,15 @@ L.LLLLL.LL";
 ##...##
 #.#.#.#
 .##.##.",
-        );
-        assert_eq!(grid.num_occupied_visible((3, 3)), 0);
+        )
+        .unwrap();
+        assert_eq!(num_occupied_visible(&grid, (3, 3)), 0);
+        assert_eq!(grid.to_string().lines().nth(3), Some("...L..."));
     }
 
     #[test]
     fn aoc11_run_2() {
-        assert_eq!(super::run_2(INPUT), 26)
+        let grid = super::Day11::parse(INPUT).unwrap();
+        assert_eq!(super::run_2(&grid), 26)
     }
 }
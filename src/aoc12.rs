@@ -1,5 +1,6 @@
+use crate::error::Result;
 use crate::helper::*;
-use std::fs;
+use crate::solution::{Answer, Solution};
 
 use nom::{
     branch::alt,
@@ -9,7 +10,7 @@ use nom::{
 };
 
 #[derive(Debug)]
-enum Cmd {
+pub enum Cmd {
     East(isize),
     West(isize),
     North(isize),
@@ -44,10 +45,23 @@ fn parse(i: &str) -> IResult<&str, Vec<Cmd>> {
     )(i)
 }
 
-pub fn run() {
-    let input = fs::read_to_string("day12.txt").unwrap();
-    println!("12:1 {}", run_1(&input));
-    println!("12:2 {}", run_2(&input));
+pub struct Day12;
+
+impl Solution for Day12 {
+    const DAY: usize = 12;
+    type Input = Vec<Cmd>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_day(Self::DAY, input, parse)
+    }
+
+    fn part1(cmds: &Self::Input) -> Result<Answer> {
+        Ok(run_1(cmds).into())
+    }
+
+    fn part2(cmds: &Self::Input) -> Result<Answer> {
+        Ok(run_2(cmds).into())
+    }
 }
 
 type Coord = (isize, isize);
@@ -95,18 +109,16 @@ impl State {
     }
 }
 
-fn run_1(input: &str) -> isize {
-    let (_, cmds) = parse(input).unwrap();
+fn run_1(cmds: &[Cmd]) -> isize {
     let mut state = State::new();
     for cmd in cmds {
-        state.take_command(&cmd);
+        state.take_command(cmd);
     }
 
     state.x.abs() + state.y.abs()
 }
 
-fn run_2(input: &str) -> isize {
-    let (_, cmds) = parse(input).unwrap();
+fn run_2(cmds: &[Cmd]) -> isize {
     let mut ship_pos = (0, 0);
     let mut waypt = (10, 1);
     for cmd in cmds {
@@ -121,7 +133,7 @@ fn run_2(input: &str) -> isize {
                 ship_pos.1 += v * dir.1;
             }
             Cmd::Left(v) => {
-                waypt = rotate_ccw(waypt, v);
+                waypt = rotate_ccw(waypt, *v);
             }
             Cmd::Rigth(v) => {
                 waypt = rotate_ccw(waypt, -v);
@@ -133,6 +145,8 @@ fn run_2(input: &str) -> isize {
 
 #[cfg(test)]
 mod tests {
+    use crate::solution::Solution;
+
     const INPUT: &str = "F10
 N3
 F7
@@ -147,11 +161,13 @@ F11";
 
     #[test]
     fn aoc12_run_1() {
-        assert_eq!(super::run_1(INPUT), 25);
+        let cmds = super::Day12::parse(INPUT).unwrap();
+        assert_eq!(super::run_1(&cmds), 25);
     }
 
     #[test]
     fn aoc12_run_2() {
-        assert_eq!(super::run_2(INPUT), 286);
+        let cmds = super::Day12::parse(INPUT).unwrap();
+        assert_eq!(super::run_2(&cmds), 286);
     }
 }
@@ -1,21 +1,38 @@
-use std::fs;
-
 use super::helper::*;
+use crate::error::{Error, Result};
+use crate::solution::{Answer, Solution};
 use nom::branch::alt;
 use nom::character::complete::{char, newline};
-use nom::combinator::map;
+use nom::combinator::{map, verify};
 use nom::multi::separated_list1;
 use nom::IResult;
-use std::collections::HashMap;
 
-pub fn run() {
-    let input = fs::read_to_string("day13.txt").unwrap();
-    println!("13:1 {}", run_1(&input));
-    println!("13:2 {}", run_2(&input));
+pub struct Day13;
+
+impl Solution for Day13 {
+    const DAY: usize = 13;
+    type Input = Notes;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_day(Self::DAY, input, parse)
+    }
+
+    fn part1(notes: &Self::Input) -> Result<Answer> {
+        run_1(notes).map(Answer::from).ok_or(Error::NoSolution {
+            day: Self::DAY,
+            part: 1,
+        })
+    }
+
+    fn part2(notes: &Self::Input) -> Result<Answer> {
+        run_2(notes).map(Answer::from).ok_or(Error::NoSolution {
+            day: Self::DAY,
+            part: 2,
+        })
+    }
 }
 
-fn run_1(input: &str) -> usize {
-    let (_, notes) = parse(input).unwrap();
+fn run_1(notes: &Notes) -> Option<usize> {
     let mults = notes
         .buses
         .iter()
@@ -26,51 +43,53 @@ fn run_1(input: &str) -> usize {
     let wait_time = mults
         .iter()
         .map(|(bus, mult)| (bus, bus * mult - notes.earliest_at))
-        .min_by(|(_, t), (_, t2)| t.cmp(t2))
-        .unwrap();
+        .min_by(|(_, t), (_, t2)| t.cmp(t2))?;
     // Multiplys bus id with wait_time
-    wait_time.0 * wait_time.1
+    Some(wait_time.0 * wait_time.1)
 }
 
-fn run_2(input: &str) -> u128 {
-    let (_, notes) = parse(input).unwrap();
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn run_2(notes: &Notes) -> Option<u128> {
     let mut buses = notes
         .buses
         .iter()
         .enumerate()
         .filter_map(|(idx, bus)| bus.map(|bus| (bus as i128, idx as i128)))
         .collect::<Vec<_>>();
+    if buses.is_empty() {
+        return None;
+    }
     buses.sort_by(|(b1, _), (b2, _)| b2.cmp(b1));
 
-    let mods: HashMap<i128, i128> = buses
-        .iter()
-        .map(|(bus, idx)| (*bus, -idx % bus))
-        .map(|(bus, idx)| (bus, if idx < 0 { bus + idx } else { idx }))
-        .collect::<HashMap<_, _>>();
-
-    let mut r = buses[0].0;
-    let mut val = *mods.get(&r).unwrap();
-
-    for (b, _) in &buses[1..] {
-        let m = *mods.get(b).unwrap();
-        while (val % *b) != m {
-            // dbg! {(r,val)};
-            val += r;
-        }
-        r *= b;
+    // Times `val + k * r` keep all buses so far in place
+    let (mut r, mut val) = (1, 0);
+    for (b, idx) in &buses {
+        let m = (-idx).rem_euclid(*b);
+        // The remainders repeat after `b` steps, if none fits none ever will
+        let k = (0..*b).find(|k| (val + k * r) % b == m)?;
+        val += k * r;
+        r = r / gcd(r, *b) * b;
     }
 
-    val as u128
+    Some(val as u128)
 }
 
 #[derive(Debug)]
-struct Notes {
+pub struct Notes {
     earliest_at: usize,
     buses: Vec<Option<usize>>,
 }
 
 fn bus(i: &str) -> IResult<&str, Option<usize>> {
-    alt((map(char('x'), |_| None), map(uval, |v| Some(v))))(i)
+    let id = verify(uval, |id: &usize| *id > 0);
+    alt((map(char('x'), |_| None), map(id, Some)))(i)
 }
 
 fn buses(i: &str) -> IResult<&str, Vec<Option<usize>>> {
@@ -86,27 +105,59 @@ fn parse(i: &str) -> IResult<&str, Notes> {
 
 #[cfg(test)]
 mod tests {
+    use crate::solution::Solution;
+
     const INPUT: &str = "939
 7,13,x,x,59,x,31,19";
 
     #[test]
     fn aoc13_1() {
-        assert_eq!(super::run_1(INPUT), 295);
+        let notes = super::Day13::parse(INPUT).unwrap();
+        assert_eq!(super::run_1(&notes), Some(295));
     }
 
     #[test]
     fn aoc13_2() {
         let input = "0\n7,13,x,x,59,x,31,19";
-        assert_eq!(super::run_2(input), 1068781);
+        assert_eq!(
+            super::run_2(&super::Day13::parse(input).unwrap()),
+            Some(1068781)
+        );
         let input = "0\n17,x,13,19";
-        assert_eq!(super::run_2(input), 3417);
+        assert_eq!(
+            super::run_2(&super::Day13::parse(input).unwrap()),
+            Some(3417)
+        );
         let input = "0\n67,7,59,61";
-        assert_eq!(super::run_2(input), 754018);
+        assert_eq!(
+            super::run_2(&super::Day13::parse(input).unwrap()),
+            Some(754018)
+        );
         let input = "0\n67,x,7,59,61";
-        assert_eq!(super::run_2(input), 779210);
+        assert_eq!(
+            super::run_2(&super::Day13::parse(input).unwrap()),
+            Some(779210)
+        );
         let input = "0\n67,7,x,59,61";
-        assert_eq!(super::run_2(input), 1261476);
+        assert_eq!(
+            super::run_2(&super::Day13::parse(input).unwrap()),
+            Some(1261476)
+        );
         let input = "0\n1789,37,47,1889";
-        assert_eq!(super::run_2(input), 1202161486);
+        assert_eq!(
+            super::run_2(&super::Day13::parse(input).unwrap()),
+            Some(1202161486)
+        );
+    }
+
+    #[test]
+    fn aoc13_no_buses() {
+        let notes = super::Day13::parse("939\nx,x").unwrap();
+        assert_eq!(super::run_1(&notes), None);
+        assert_eq!(super::run_2(&notes), None);
+        assert!(super::Day13::parse("939\n7,0").is_err());
+        // Never 1 apart
+        let notes = super::Day13::parse("939\n2,4").unwrap();
+        assert_eq!(super::run_2(&notes), None);
     }
 }
@@ -1,4 +1,6 @@
-use crate::helper::uval;
+use crate::error::Result;
+use crate::helper::{parse_day, uval};
+use crate::solution::{Answer, Solution};
 use nom::{
     bytes::complete::tag,
     character::complete::{newline, one_of},
@@ -8,20 +10,33 @@ use nom::{
 };
 
 use std::collections::HashMap;
-use std::fs;
 
-pub fn run() {
-    let input = fs::read_to_string("day14.txt").unwrap();
-    println!("14:1 {}", run_1(&input));
-    println!("14:2 {}", run_2(&input));
-}
+pub struct Day14;
+
+impl Solution for Day14 {
+    const DAY: usize = 14;
+    // The two parts interpret the masks differently, so keep both
+    type Input = (Vec<Program1>, Vec<Program2>);
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let programs_1 = parse_day(Self::DAY, input, parse_1)?;
+        let programs_2 = parse_day(Self::DAY, input, parse_2)?;
+        Ok((programs_1, programs_2))
+    }
+
+    fn part1((programs, _): &Self::Input) -> Result<Answer> {
+        Ok(run_1(programs).into())
+    }
 
-fn run_1(input: &str) -> u64 {
-    let (_, programs) = parse_1(input).unwrap();
+    fn part2((_, programs): &Self::Input) -> Result<Answer> {
+        Ok(run_2(programs).into())
+    }
+}
 
+fn run_1(programs: &[Program1]) -> u64 {
     let mut mem = HashMap::new();
 
-    for program in &programs {
+    for program in programs {
         for (addr, val) in &program.mem_set {
             let mem_loc = mem.entry(addr).or_insert(0);
             *mem_loc = *val;
@@ -30,28 +45,24 @@ fn run_1(input: &str) -> u64 {
         }
     }
 
-    mem.iter().fold(0, |acc, (_, v)| acc + v)
+    mem.values().sum()
 }
 
-fn run_2(input: &str) -> u64 {
-    let (_, mut programs) = parse_2(input).unwrap();
-
+fn run_2(programs: &[Program2]) -> u64 {
     let mut mem: HashMap<u64, u64> = HashMap::new();
 
-    for program in programs.iter_mut() {
-        for (address, value) in program.mem_set.iter_mut() {
+    for program in programs {
+        for (address, value) in program.mem_set.iter() {
+            let mut address = *address;
             // First set all bits that should be one
             for (i, m) in program.mask.iter().enumerate() {
                 let bit = program.mask.len() - 1 - i;
-                match m {
-                    Some(1) => {
-                        *address |= 1 << bit;
-                    }
-                    _ => (),
+                if let Some(1) = m {
+                    address |= 1 << bit;
                 }
             }
 
-            let mut addresses: Vec<u64> = vec![*address];
+            let mut addresses: Vec<u64> = vec![address];
 
             for (i, m) in program.mask.iter().enumerate() {
                 let bit = program.mask.len() - 1 - i;
@@ -73,7 +84,7 @@ fn run_2(input: &str) -> u64 {
         }
     }
 
-    mem.iter().fold(0, |acc, (_, v)| acc + v)
+    mem.values().sum()
 }
 
 fn parse_mask_1(i: &str) -> IResult<&str, (u64, u64)> {
@@ -89,9 +100,7 @@ fn parse_mask_1(i: &str) -> IResult<&str, (u64, u64)> {
                 or_mask |= 1;
                 and_mask |= 1;
             }
-            '0' => {
-                and_mask += 0;
-            }
+            '0' => (),
             _ => {
                 and_mask |= 1;
             }
@@ -109,7 +118,7 @@ fn parse_mem(i: &str) -> IResult<&str, (u64, u64)> {
 }
 
 #[derive(Debug)]
-struct Program1 {
+pub struct Program1 {
     or_mask: u64,
     and_mask: u64,
     mem_set: Vec<(u64, u64)>,
@@ -135,7 +144,7 @@ fn parse_1(i: &str) -> IResult<&str, Vec<Program1>> {
 }
 
 #[derive(Debug)]
-struct Program2 {
+pub struct Program2 {
     mask: Vec<Option<u8>>,
     mem_set: Vec<(u64, u64)>,
 }
@@ -180,6 +189,8 @@ fn parse_2(i: &str) -> IResult<&str, Vec<Program2>> {
 
 #[cfg(test)]
 mod tests {
+    use crate::solution::Solution;
+
     const INPUT: &str = "mask = XXXXXXXXXXXXXXXXXXXXXXXXXXXXX1XXXX0X
 mem[8] = 11
 mem[7] = 101
@@ -207,7 +218,8 @@ mem[8] = 0";
 
     #[test]
     fn aoc14_run_1() {
-        assert_eq!(super::run_1(INPUT), 165);
+        let (programs, _) = super::Day14::parse(INPUT).unwrap();
+        assert_eq!(super::run_1(&programs), 165);
     }
 
     #[test]
@@ -216,6 +228,7 @@ mem[8] = 0";
 mem[42] = 100
 mask = 00000000000000000000000000000000X0XX
 mem[26] = 1";
-        assert_eq!(super::run_2(input), 208);
+        let (_, programs) = super::Day14::parse(input).unwrap();
+        assert_eq!(super::run_2(&programs), 208);
     }
 }
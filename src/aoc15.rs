@@ -1,20 +1,28 @@
+use crate::error::Result;
+use crate::helper::parse_day;
+use crate::solution::{Answer, Solution};
 use std::collections::HashMap;
-use std::fs;
 
-pub fn run() {
-    let input = fs::read_to_string("day15.txt").unwrap();
-    println!("15:1 {}", run_1(&input));
-    println!("15:2 {}", run_2(&input));
-}
+pub struct Day15;
+
+impl Solution for Day15 {
+    const DAY: usize = 15;
+    type Input = Vec<usize>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_day(Self::DAY, input, parse)
+    }
 
-#[derive(Debug, Clone)]
-struct Spoken {
-    turn: usize,
-    cnt: usize,
+    fn part1(vals: &Self::Input) -> Result<Answer> {
+        Ok(run_1(vals).into())
+    }
+
+    fn part2(vals: &Self::Input) -> Result<Answer> {
+        Ok(run_2(vals).into())
+    }
 }
 
-fn solve(input: &str, break_at_turn: usize) -> usize {
-    let (_, vals) = parse(input).unwrap();
+fn solve(vals: &[usize], break_at_turn: usize) -> usize {
     let mut last_spoken_lu = HashMap::new();
 
     for (t, v) in vals.iter().enumerate() {
@@ -30,6 +38,9 @@ fn solve(input: &str, break_at_turn: usize) -> usize {
         }
 
         turn += 1;
+        if turn.is_multiple_of(1 << 16) {
+            crate::runner::checkpoint();
+        }
         let s = last_spoken_lu.get_mut(&last_spoken).unwrap();
         if s.len() == 1 {
             // last time this was spoken, it was the first time
@@ -39,7 +50,7 @@ fn solve(input: &str, break_at_turn: usize) -> usize {
             last_spoken = s[s.len() - 1] - s[s.len() - 2];
         }
         //
-        let e = last_spoken_lu.entry(last_spoken).or_insert(vec![]);
+        let e = last_spoken_lu.entry(last_spoken).or_default();
         e.push(turn);
         if e.len() > 2 {
             e.remove(0);
@@ -47,12 +58,12 @@ fn solve(input: &str, break_at_turn: usize) -> usize {
     }
 }
 
-fn run_1(input: &str) -> usize {
-    solve(input, 2020)
+fn run_1(vals: &[usize]) -> usize {
+    solve(vals, 2020)
 }
 
-fn run_2(input: &str) -> usize {
-    solve(input, 30_000_000)
+fn run_2(vals: &[usize]) -> usize {
+    solve(vals, 30_000_000)
 }
 
 fn parse(i: &str) -> nom::IResult<&str, Vec<usize>> {
@@ -63,27 +74,29 @@ fn parse(i: &str) -> nom::IResult<&str, Vec<usize>> {
 
 #[cfg(test)]
 mod tests {
+    use crate::solution::Solution;
 
     #[test]
     fn aoc15_run_1() {
-        assert_eq!(super::run_1("0,3,6"), 436);
-        assert_eq!(super::run_1("1,3,2"), 1);
-        assert_eq!(super::run_1("2,1,3"), 10);
-        assert_eq!(super::run_1("1,2,3"), 27);
-        assert_eq!(super::run_1("2,3,1"), 78);
-        assert_eq!(super::run_1("3,2,1"), 438);
-        assert_eq!(super::run_1("3,1,2"), 1836);
+        let run_1 = |i| super::run_1(&super::Day15::parse(i).unwrap());
+        assert_eq!(run_1("0,3,6"), 436);
+        assert_eq!(run_1("1,3,2"), 1);
+        assert_eq!(run_1("2,1,3"), 10);
+        assert_eq!(run_1("1,2,3"), 27);
+        assert_eq!(run_1("2,3,1"), 78);
+        assert_eq!(run_1("3,2,1"), 438);
+        assert_eq!(run_1("3,1,2"), 1836);
     }
 
     #[test]
     fn aoc15_run_2() {
         // Correct, but takes a long time to run
-        // assert_eq!(super::run_2("0,3,6"), 175594);
-        // assert_eq!(super::run_2("1,3,2"), 2578);
-        // assert_eq!(super::run_2("2,1,3"), 3544142);
-        // assert_eq!(super::run_2("1,2,3"), 261214);
-        // assert_eq!(super::run_2("2,3,1"), 6895259);
-        // assert_eq!(super::run_2("3,2,1"), 18);
-        // assert_eq!(super::run_2("3,1,2"), 362);
+        // assert_eq!(super::run_2(&super::Day15::parse("0,3,6").unwrap()), 175594);
+        // assert_eq!(super::run_2(&super::Day15::parse("1,3,2").unwrap()), 2578);
+        // assert_eq!(super::run_2(&super::Day15::parse("2,1,3").unwrap()), 3544142);
+        // assert_eq!(super::run_2(&super::Day15::parse("1,2,3").unwrap()), 261214);
+        // assert_eq!(super::run_2(&super::Day15::parse("2,3,1").unwrap()), 6895259);
+        // assert_eq!(super::run_2(&super::Day15::parse("3,2,1").unwrap()), 18);
+        // assert_eq!(super::run_2(&super::Day15::parse("3,1,2").unwrap()), 362);
     }
 }
@@ -1,26 +1,41 @@
+use crate::error::{Error, Result};
+use crate::helper::parse_day;
+use crate::solution::{Answer, Solution};
 use std::collections::HashMap;
 use std::collections::VecDeque;
-use std::fs;
 
 use nom::{
     bytes::complete::tag,
     character::complete::{newline, none_of},
-    combinator::recognize,
+    combinator::{recognize, verify},
     multi::{many1, separated_list1},
     sequence::separated_pair,
     IResult,
 };
 
-pub fn run() {
-    let input = fs::read_to_string("day16.txt").unwrap();
+pub struct Day16;
 
-    println!("16:1 - {:?}", run_1(&input));
-    println!("16:2 - {:?}", run_2(&input));
-}
+impl Solution for Day16 {
+    const DAY: usize = 16;
+    type Input = Notes;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_day(Self::DAY, input, parse)
+    }
 
-fn run_1(input: &str) -> usize {
-    let (_, notes) = parse(input).unwrap();
+    fn part1(notes: &Self::Input) -> Result<Answer> {
+        Ok(run_1(notes).into())
+    }
 
+    fn part2(notes: &Self::Input) -> Result<Answer> {
+        run_2(notes).map(Answer::from).ok_or(Error::NoSolution {
+            day: Self::DAY,
+            part: 2,
+        })
+    }
+}
+
+fn run_1(notes: &Notes) -> usize {
     notes
         .nearby_tickets
         .iter()
@@ -36,31 +51,34 @@ fn run_1(input: &str) -> usize {
         .sum()
 }
 
-fn solve_2(input: &str) -> HashMap<String, usize> {
-    let (_, notes) = parse(input).unwrap();
+/// The value of each field on my ticket, or `None` if the tickets don't
+/// tell which index each field is at.
+fn solve_2(notes: &Notes) -> Option<HashMap<String, usize>> {
     let valid_tickets = notes
         .nearby_tickets
         .iter()
         .filter(|ticket| {
-            let is_valid = ticket.iter().all(|value| {
+            ticket.iter().all(|value| {
                 notes.field_ranges.iter().any(|(_, range)| {
                     range
                         .iter()
                         .any(|(low, high)| value >= low && value <= high)
                 })
-            });
-            is_valid
+            })
         })
         .collect::<Vec<_>>();
 
     let mut validations: HashMap<String, Vec<FieldRange>> =
-        notes.field_ranges.into_iter().collect();
-    let my_ticket = notes.my_ticket;
+        notes.field_ranges.iter().cloned().collect();
+    let my_ticket = &notes.my_ticket;
     let mut name_to_index: HashMap<String, usize> = HashMap::new();
 
     let mut indices_to_check: VecDeque<usize> = (0..validations.len()).collect();
+    // Indices requeued since one was last resolved
+    let mut stalled = 0;
 
     while let Some(idx) = indices_to_check.pop_front() {
+        crate::runner::checkpoint();
         // Search valid tickets for a validation that matches all
         // values at this idx
         let values_at_idx = valid_tickets
@@ -88,36 +106,44 @@ fn solve_2(input: &str) -> HashMap<String, usize> {
             let name = valids.remove(0);
             validations.remove(&name);
             name_to_index.insert(name, idx);
+            stalled = 0;
+        } else if valids.is_empty() {
+            return None;
         } else {
-            // Otherwise, recheck it later
+            // Otherwise, recheck it later, unless a whole pass over the
+            // remaining indices resolved none of them
             indices_to_check.push_back(idx);
+            stalled += 1;
+            if stalled == indices_to_check.len() {
+                return None;
+            }
         }
     }
 
-    name_to_index
+    let fields = name_to_index
         .into_iter()
         .map(|(k, idx)| (k, my_ticket[idx]))
-        .collect()
+        .collect();
+    Some(fields)
 }
 
-fn run_2(input: &str) -> usize {
-    let fields = solve_2(input);
+fn run_2(notes: &Notes) -> Option<usize> {
+    let fields = solve_2(notes)?;
 
-    fields
-        .iter()
-        .filter_map(|(k, v)| {
-            if k.starts_with("departure") {
-                Some(*v)
-            } else {
-                None
-            }
-        })
-        .fold(1, |acc, v| acc * v)
+    let departures = fields.iter().filter_map(|(k, v)| {
+        if k.starts_with("departure") {
+            Some(*v)
+        } else {
+            None
+        }
+    });
+    Some(departures.product())
 }
 
 type FieldRange = (usize, usize);
 
-struct Notes {
+#[derive(Debug)]
+pub struct Notes {
     field_ranges: Vec<(String, Vec<FieldRange>)>,
     my_ticket: Vec<usize>,
     nearby_tickets: Vec<Vec<usize>>,
@@ -134,16 +160,20 @@ fn parse_fieldrange(i: &str) -> IResult<&str, (String, Vec<FieldRange>)> {
 fn parse(i: &str) -> IResult<&str, Notes> {
     let (i, field_ranges) = separated_list1(newline, parse_fieldrange)(i)?;
     let (i, _) = many1(newline)(i)?;
+    // Every ticket has a value for each field
+    let ticket = || {
+        let values = separated_list1(tag(","), crate::helper::uval);
+        verify(values, |t: &Vec<usize>| t.len() == field_ranges.len())
+    };
 
     let (i, _) = tag("your ticket:")(i)?;
     let (i, _) = newline(i)?;
-    let (i, my_ticket) = separated_list1(tag(","), crate::helper::uval)(i)?;
+    let (i, my_ticket) = ticket()(i)?;
     let (i, _) = many1(newline)(i)?;
 
     let (i, _) = tag("nearby tickets:")(i)?;
     let (i, _) = newline(i)?;
-    let (i, nearby_tickets) =
-        separated_list1(newline, separated_list1(tag(","), crate::helper::uval))(i)?;
+    let (i, nearby_tickets) = separated_list1(newline, ticket())(i)?;
     Ok((
         i,
         Notes {
@@ -156,6 +186,8 @@ fn parse(i: &str) -> IResult<&str, Notes> {
 
 #[cfg(test)]
 mod tests {
+    use crate::solution::Solution;
+
     const INPUT_1: &str = "class: 1-3 or 5-7
 row: 6-11 or 33-44
 seat: 13-40 or 45-50
@@ -188,7 +220,8 @@ nearby tickets:
 
     #[test]
     fn aoc16_run_1() {
-        assert_eq!(super::run_1(INPUT_1), 71);
+        let notes = super::Day16::parse(INPUT_1).unwrap();
+        assert_eq!(super::run_1(&notes), 71);
     }
     const INPUT_2: &str = "class: 0-1 or 4-19
 row: 0-5 or 8-19
@@ -204,10 +237,32 @@ nearby tickets:
 
     #[test]
     fn aoc16_run_2() {
-        let fields = super::solve_2(INPUT_2);
+        let notes = super::Day16::parse(INPUT_2).unwrap();
+        let fields = super::solve_2(&notes).unwrap();
         assert_eq!(fields.get("row").unwrap(), &11);
         assert_eq!(fields.get("class").unwrap(), &12);
         assert_eq!(fields.get("seat").unwrap(), &13);
-        //
+    }
+
+    #[test]
+    fn aoc16_unsolvable() {
+        // Either field fits either index
+        let input = "a: 1-3 or 5-7\nb: 1-3 or 5-7\n\nyour ticket:\n7,3\n\nnearby tickets:\n7,3";
+        let notes = super::Day16::parse(input).unwrap();
+        assert_eq!(super::run_2(&notes), None);
+        // No field fits the second index
+        let input = "a: 1-3\nb: 5-7\n\nyour ticket:\n1,2\n\nnearby tickets:\n1,2";
+        let notes = super::Day16::parse(input).unwrap();
+        assert_eq!(super::run_2(&notes), None);
+
+        let error = |input: &str| super::Day16::parse(input).unwrap_err().to_string();
+        assert_eq!(
+            error("a: 1-3\nb: 5-7\n\nyour ticket:\n1\n\nnearby tickets:\n1,5"),
+            "day 16, line 5, column 1: cannot parse \"1\""
+        );
+        assert_eq!(
+            error("a: 1-3\nb: 5-7\n\nyour ticket:\n1,5\n\nnearby tickets:\n1,5\n5"),
+            "day 16, line 9, column 1: cannot parse \"5\""
+        );
     }
 }
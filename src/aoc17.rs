@@ -1,169 +1,124 @@
-use std::collections::{HashMap, HashSet};
-use std::fs;
-
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
-struct Coord {
-    x: i64,
-    y: i64,
-    z: i64,
+use crate::automaton::{Automaton, FoldedLattice, Rule};
+use crate::error::Result;
+use crate::helper::Grid;
+use crate::solution::{Answer, Solution};
+
+/// The active cubes of the starting slice, as `[x, y]`.
+pub type Map = Vec<[i64; 2]>;
+
+fn parse(input: &str) -> Result<Map> {
+    let grid = Grid::parse(input, |c| match c {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None,
+    })?;
+    let active = grid.iter().filter(|(_, active)| **active);
+    Ok(active.map(|((x, y), _)| [x as i64, y as i64]).collect())
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Hash)]
-struct Coord4 {
-    x: i64,
-    y: i64,
-    z: i64,
-    w: i64,
-}
-
-type Map = HashSet<Coord>;
-
-fn parse(input: &str) -> Map {
-    let mut res = HashSet::new();
-    for (y, line) in input.lines().enumerate() {
-        for (x, c) in line.chars().enumerate() {
-            if c == '#' {
-                res.insert(Coord {
-                    x: x as i64,
-                    y: y as i64,
-                    z: 0,
-                });
-            }
-        }
-    }
-    res
-}
+pub struct Day17;
 
-pub fn run() {
-    let input = fs::read_to_string("day17.txt").unwrap();
+impl Solution for Day17 {
+    const DAY: usize = 17;
+    type Input = Map;
 
-    println!("day17-1: {}", run_1(&input));
-    println!("day17-2: {}", run_2(&input));
-}
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input).map_err(|e| e.for_day(Self::DAY))
+    }
 
-fn add_nbrs(c: &Coord, nbrs: &mut HashMap<Coord, HashSet<Coord>>) {
-    for dz in &[-1, 0, 1] {
-        for dy in &[-1, 0, 1] {
-            for dx in &[-1, 0, 1] {
-                if (dx, dy, dz) != (&0, &0, &0) {
-                    let nbr = Coord {
-                        x: c.x + dx,
-                        y: c.y + dy,
-                        z: c.z + dz,
-                    };
-                    nbrs.entry((*c).clone())
-                        .or_insert(HashSet::new())
-                        .insert(nbr.clone());
-                    nbrs.entry(nbr)
-                        .or_insert(HashSet::new())
-                        .insert((*c).clone());
-                }
-            }
-        }
+    fn part1(map: &Self::Input) -> Result<Answer> {
+        Ok(run_1(map).into())
     }
-}
 
-fn add_nbrs4(c: &Coord4, nbrs: &mut HashMap<Coord4, HashSet<Coord4>>) {
-    for dw in &[-1, 0, 1] {
-        for dz in &[-1, 0, 1] {
-            for dy in &[-1, 0, 1] {
-                for dx in &[-1, 0, 1] {
-                    if (dx, dy, dz, dw) != (&0, &0, &0, &0) {
-                        let nbr = Coord4 {
-                            x: c.x + dx,
-                            y: c.y + dy,
-                            z: c.z + dz,
-                            w: c.w + dw,
-                        };
-                        nbrs.entry((*c).clone())
-                            .or_insert(HashSet::new())
-                            .insert(nbr.clone());
-                        nbrs.entry(nbr)
-                            .or_insert(HashSet::new())
-                            .insert((*c).clone());
-                    }
-                }
-            }
-        }
+    fn part2(map: &Self::Input) -> Result<Answer> {
+        Ok(run_2(map).into())
     }
 }
 
-fn run_1(input: &str) -> usize {
-    let mut state = parse(input);
-    let mut new_state = HashSet::new();
-    for _ in 0..6 {
-        new_state.clear();
-        let mut cache = HashMap::new();
-        state.iter().for_each(|c| add_nbrs(c, &mut cache));
-        for (cube, nbrs) in cache.into_iter() {
-            let active_nbrs = nbrs.iter().filter(|n| state.contains(n)).count();
-            if state.contains(&cube) && (active_nbrs == 2 || active_nbrs == 3) {
-                new_state.insert(cube);
-            } else if !state.contains(&cube) && active_nbrs == 3 {
-                new_state.insert(cube);
-            }
-        }
-
-        std::mem::swap(&mut state, &mut new_state);
-        // dbg! {&state};
-        // panic!();
-    }
+/// The number of boot cycles the puzzle asks for.
+pub const CYCLES: usize = 6;
+
+/// The most dimensions `boot_in` runs in.
+pub const MAX_DIMENSIONS: usize = 8;
+
+/// The number of active cubes after `cycles` cycles, with the starting slice
+/// in `N` dimensions, at least 2.
+///
+/// All but the first two coordinates start at 0, so negating any of them
+/// or swapping two changes nothing, and the lattice is folded along those
+/// symmetries.
+pub fn boot<const N: usize>(map: &Map, cycles: usize) -> usize {
+    let active = map.iter().map(|[x, y]| {
+        let mut cube = [0; N];
+        cube[0] = *x;
+        cube[1] = *y;
+        cube
+    });
+    let lattice = FoldedLattice::<N> { free: 2 };
+    let mut automaton = Automaton::new(lattice, Rule::life(), active);
+    automaton.run(Some(cycles));
+    automaton.population()
+}
 
-    state.len()
+/// `boot` in a number of dimensions chosen at runtime, or `None` if it is
+/// less than 2 or more than `MAX_DIMENSIONS`.
+pub fn boot_in(map: &Map, dimensions: usize, cycles: usize) -> Option<usize> {
+    let active = match dimensions {
+        2 => boot::<2>(map, cycles),
+        3 => boot::<3>(map, cycles),
+        4 => boot::<4>(map, cycles),
+        5 => boot::<5>(map, cycles),
+        6 => boot::<6>(map, cycles),
+        7 => boot::<7>(map, cycles),
+        8 => boot::<8>(map, cycles),
+        _ => return None,
+    };
+    Some(active)
 }
 
-fn run_2(input: &str) -> usize {
-    let state = parse(input);
-    let mut state = state
-        .iter()
-        .map(|c| Coord4 {
-            x: c.x,
-            y: c.y,
-            z: c.z,
-            w: 0,
-        })
-        .collect::<HashSet<_>>();
-
-    let mut new_state = HashSet::new();
-    for _ in 0..6 {
-        new_state.clear();
-        let mut cache = HashMap::new();
-        state.iter().for_each(|c| add_nbrs4(c, &mut cache));
-        for (cube, nbrs) in cache.into_iter() {
-            let active_nbrs = nbrs.iter().filter(|n| state.contains(n)).count();
-            if state.contains(&cube) && (active_nbrs == 2 || active_nbrs == 3) {
-                new_state.insert(cube);
-            } else if !state.contains(&cube) && active_nbrs == 3 {
-                new_state.insert(cube);
-            }
-        }
-
-        std::mem::swap(&mut state, &mut new_state);
-    }
+fn run_1(map: &Map) -> usize {
+    boot::<3>(map, CYCLES)
+}
 
-    state.len()
+fn run_2(map: &Map) -> usize {
+    boot::<4>(map, CYCLES)
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::solution::Solution;
+
     const INPUT: &str = ".#.
 ..#
 ###";
 
     #[test]
     fn aoc17_parse() {
-        let map = super::parse(INPUT);
+        let map = super::parse(INPUT).unwrap();
 
         assert_eq!(map.len(), 5);
     }
 
     #[test]
     fn aoc17_run_1() {
-        assert_eq!(super::run_1(INPUT), 112);
+        let map = super::Day17::parse(INPUT).unwrap();
+        assert_eq!(super::run_1(&map), 112);
     }
 
     #[test]
     fn aoc17_run_2() {
-        assert_eq!(super::run_2(INPUT), 848);
+        let map = super::Day17::parse(INPUT).unwrap();
+        assert_eq!(super::run_2(&map), 848);
+    }
+
+    #[test]
+    fn aoc17_dimensions() {
+        let map = super::Day17::parse(INPUT).unwrap();
+        assert_eq!(super::boot_in(&map, 3, 6), Some(112));
+        assert_eq!(super::boot_in(&map, 5, 6), Some(5760));
+        assert_eq!(super::boot_in(&map, 6, 6), Some(35936));
+        // A glider on the plane
+        assert_eq!(super::boot_in(&map, 2, 4), Some(5));
+        assert_eq!(super::boot_in(&map, 9, 6), None);
     }
 }
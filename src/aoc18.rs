@@ -1,11 +1,38 @@
-pub fn run() {
-    let input = std::fs::read_to_string("day18.txt").unwrap();
-    println!("18:1: {}", run_1(&input));
-    println!("18:2: {}", run_2(&input));
+use crate::error::{Error, Result};
+use crate::helper::parse_day;
+use crate::solution::{Answer, Solution};
+
+pub struct Day18;
+
+impl Solution for Day18 {
+    const DAY: usize = 18;
+    type Input = Vec<Vec<Token>>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_day(
+            Self::DAY,
+            input,
+            nom::multi::separated_list1(nom::character::complete::newline, parse_expr),
+        )
+    }
+
+    fn part1(exprs: &Self::Input) -> Result<Answer> {
+        run_1(exprs).map(Answer::from).ok_or(Error::NoSolution {
+            day: Self::DAY,
+            part: 1,
+        })
+    }
+
+    fn part2(exprs: &Self::Input) -> Result<Answer> {
+        run_2(exprs).map(Answer::from).ok_or(Error::NoSolution {
+            day: Self::DAY,
+            part: 2,
+        })
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
-enum Token {
+pub enum Token {
     Num(usize),
     Add,
     Mul,
@@ -33,56 +60,79 @@ fn parse_tokens(i: &str) -> nom::IResult<&str, Vec<Token>> {
     nom::multi::many1(token)(i)
 }
 
-fn eval_inner_1(mut idx: usize, tokens: &[Token]) -> (usize, usize) {
+/// Whether `tokens` alternate between values and operators, starting and
+/// ending with a value, with every paren closed.
+fn well_formed(tokens: &[Token]) -> bool {
+    let mut depth = 0usize;
+    let mut want_value = true;
+    for token in tokens {
+        match (token, want_value) {
+            (Token::Num(_), true) => want_value = false,
+            (Token::OpenParen, true) => depth += 1,
+            (Token::Add | Token::Mul, false) => want_value = true,
+            (Token::CloseParen, false) if depth > 0 => depth -= 1,
+            _ => return false,
+        }
+    }
+    !want_value && depth == 0
+}
+
+fn parse_expr(i: &str) -> nom::IResult<&str, Vec<Token>> {
+    nom::combinator::verify(parse_tokens, |tokens: &[Token]| well_formed(tokens))(i)
+}
+
+/// Evaluates left to right from `idx` up to a closing paren or the end,
+/// returning where it stopped and the value, or `None` if the expression is
+/// malformed or overflows.
+fn eval_inner_1(mut idx: usize, tokens: &[Token]) -> Option<(usize, usize)> {
     let mut res = None;
     let mut op = None;
     while idx < tokens.len() {
-        match tokens[idx] {
-            Token::Add => {
-                op = Some(Token::Add);
-            }
-            Token::Mul => {
-                op = Some(Token::Mul);
-            }
-            Token::Num(n) => match op.take() {
-                Some(Token::Add) => {
-                    res.as_mut().map(|res| *res += n);
+        let n = match tokens[idx] {
+            Token::Add | Token::Mul => {
+                // Needs a value before it, and no other operator
+                if res.is_none() || op.is_some() {
+                    return None;
                 }
-                Some(Token::Mul) => {
-                    res.as_mut().map(|res| *res *= n);
-                }
-                _ => {
-                    res = Some(n);
-                }
-            },
+                op = Some(tokens[idx].clone());
+                idx += 1;
+                continue;
+            }
+            Token::Num(n) => n,
             Token::OpenParen => {
-                let (new_idx, n) = eval_inner_1(idx + 1, tokens);
-                idx = new_idx;
-                match op.take() {
-                    Some(Token::Add) => {
-                        res.as_mut().map(|res| *res += n);
-                    }
-                    Some(Token::Mul) => {
-                        res.as_mut().map(|res| *res *= n);
-                    }
-                    _ => {
-                        res = Some(n);
-                    }
+                let (close, n) = eval_inner_1(idx + 1, tokens)?;
+                if tokens.get(close) != Some(&Token::CloseParen) {
+                    return None;
                 }
+                idx = close;
+                n
             }
             Token::CloseParen => {
                 break;
             }
-        }
+        };
+        res = match (res, op.take()) {
+            (None, None) => Some(n),
+            (Some(res), Some(Token::Add)) => Some(res.checked_add(n)?),
+            (Some(res), Some(Token::Mul)) => Some(res.checked_mul(n)?),
+            // Two values in a row
+            _ => return None,
+        };
         idx += 1;
     }
-    (idx, res.unwrap())
+    if op.is_some() {
+        return None;
+    }
+    Some((idx, res?))
 }
 
-fn eval_1(input: &str) -> usize {
-    let (_, tokens) = parse_tokens(input).unwrap();
-
-    eval_inner_1(0, &tokens).1
+fn eval_1(tokens: &[Token]) -> Option<usize> {
+    let (end, n) = eval_inner_1(0, tokens)?;
+    // Stopped early at a closing paren that was never opened
+    if end < tokens.len() {
+        return None;
+    }
+    Some(n)
 }
 
 struct RecursiveDescent {
@@ -123,107 +173,145 @@ impl RecursiveDescent {
             self.advance();
             return true;
         }
-        return false;
+        false
     }
 
-    fn primary(&mut self) -> Box<Expr> {
+    fn primary(&mut self) -> Option<Box<Expr>> {
         if let Some(Token::Num(v)) = self.peek().map(|t| (*t).clone()) {
             self.advance();
-            return Box::new(Expr::Num(v));
+            return Some(Box::new(Expr::Num(v)));
         }
 
         if self.match_tokens(&Token::OpenParen) {
-            let expr = self.factor();
-            self.advance();
-            return Box::new(Expr::Group(expr));
+            let expr = self.factor()?;
+            if !self.match_tokens(&Token::CloseParen) {
+                return None;
+            }
+            return Some(Box::new(Expr::Group(expr)));
         }
-        unreachable!()
+        None
     }
 
-    fn term(&mut self) -> Box<Expr> {
-        let mut expr = self.primary();
+    fn term(&mut self) -> Option<Box<Expr>> {
+        let mut expr = self.primary()?;
 
         while self.match_tokens(&Token::Add) {
-            let right = self.primary();
+            let right = self.primary()?;
             expr = Box::new(Expr::Add(expr, right));
         }
-        expr
+        Some(expr)
     }
 
-    fn factor(&mut self) -> Box<Expr> {
-        let mut expr = self.term();
+    fn factor(&mut self) -> Option<Box<Expr>> {
+        let mut expr = self.term()?;
 
         while self.match_tokens(&Token::Mul) {
-            let right = self.term();
+            let right = self.term()?;
             expr = Box::new(Expr::Mul(expr, right));
         }
-        expr
+        Some(expr)
     }
 }
 
-fn eval_expr(expr: &Expr) -> usize {
+/// The value of `expr`, or `None` if it overflows.
+fn eval_expr(expr: &Expr) -> Option<usize> {
     match expr {
-        Expr::Num(v) => *v,
-        Expr::Add(a, b) => eval_expr(a) + eval_expr(b),
-        Expr::Mul(a, b) => eval_expr(a) * eval_expr(b),
+        Expr::Num(v) => Some(*v),
+        Expr::Add(a, b) => eval_expr(a)?.checked_add(eval_expr(b)?),
+        Expr::Mul(a, b) => eval_expr(a)?.checked_mul(eval_expr(b)?),
         Expr::Group(a) => eval_expr(a),
     }
 }
 
-fn eval_2(input: &str) -> usize {
-    let (rest, tokens) = parse_tokens(input).unwrap();
-    assert_eq!(rest, "");
-
-    let mut rd = RecursiveDescent::new(tokens);
-    let expr = rd.factor();
+/// The value of the expression, or `None` if it is malformed or overflows.
+fn eval_2(tokens: &[Token]) -> Option<usize> {
+    let mut rd = RecursiveDescent::new(tokens.to_vec());
+    let expr = rd.factor()?;
+    if !rd.is_at_end() {
+        return None;
+    }
     eval_expr(&expr)
 }
 
-fn run_1(input: &str) -> usize {
-    input.lines().map(eval_1).sum()
+fn run_1(exprs: &[Vec<Token>]) -> Option<usize> {
+    exprs
+        .iter()
+        .try_fold(0usize, |sum, tokens| sum.checked_add(eval_1(tokens)?))
 }
 
-fn run_2(input: &str) -> usize {
-    input.lines().map(eval_2).sum()
+fn run_2(exprs: &[Vec<Token>]) -> Option<usize> {
+    exprs
+        .iter()
+        .try_fold(0usize, |sum, tokens| sum.checked_add(eval_2(tokens)?))
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::solution::Solution;
+
+    fn eval_1(input: &str) -> usize {
+        super::eval_1(&super::parse_tokens(input).unwrap().1).unwrap()
+    }
+
+    fn eval_2(input: &str) -> usize {
+        super::eval_2(&super::parse_tokens(input).unwrap().1).unwrap()
+    }
+
     #[test]
-    fn aoc18_eval_1() {
-        assert_eq!(super::eval_1("1 + 2 * 3 + 4 * 5 + 6"), 71);
-        assert_eq!(super::eval_1("2 * 3 + (4 * 5)"), 26);
-        assert_eq!(super::eval_1("5 + (8 * 3 + 9 + 3 * 4 * 3)"), 437);
+    fn aoc18_malformed() {
+        for input in &[
+            "()", "+", "1 +", "1 2", "(1 + 2", "1 + 2)", "* 3", "1 + * 2",
+        ] {
+            let tokens = super::parse_tokens(input).unwrap().1;
+            assert_eq!(super::eval_1(&tokens), None, "{}", input);
+            assert_eq!(super::eval_2(&tokens), None, "{}", input);
+        }
+        for input in &["()", "1 +", "1 2", "(1 + 2", "1 + 2)", "1 + * 2"] {
+            assert!(!super::well_formed(&super::parse_tokens(input).unwrap().1));
+        }
+        assert!(super::well_formed(
+            &super::parse_tokens("((2 * 3) + 4)").unwrap().1
+        ));
+        let error = super::Day18::parse("1 + 2\n1 + * 2").unwrap_err();
         assert_eq!(
-            super::eval_1("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))"),
-            12240
+            error.to_string(),
+            "day 18, line 2, column 1: cannot parse \"1 + * 2\""
         );
+
+        let tokens = super::parse_tokens("18446744073709551615 + 1").unwrap().1;
+        assert_eq!(super::run_1(std::slice::from_ref(&tokens)), None);
+        assert_eq!(super::run_2(&[tokens]), None);
+    }
+
+    #[test]
+    fn aoc18_eval_1() {
+        assert_eq!(eval_1("1 + 2 * 3 + 4 * 5 + 6"), 71);
+        assert_eq!(eval_1("2 * 3 + (4 * 5)"), 26);
+        assert_eq!(eval_1("5 + (8 * 3 + 9 + 3 * 4 * 3)"), 437);
+        assert_eq!(eval_1("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))"), 12240);
         assert_eq!(
-            super::eval_1("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2"),
+            eval_1("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2"),
             13632
         );
         assert_eq!(
-            super::eval_1("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2"),
+            eval_1("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2"),
             13632
         );
     }
 
     #[test]
     fn aoc18_eval_2() {
-        assert_eq!(super::eval_2("1+2*3"), 9);
-        assert_eq!(super::eval_2("1*2+3"), 5);
-        assert_eq!(super::eval_2("1*2*3"), 6);
-        assert_eq!(super::eval_2("1+(2*3)+2"), 9);
-        assert_eq!(super::eval_2("((2+4*9) * (6+9*8+6) +6)"), 11664);
-        assert_eq!(super::eval_2("1 + (2 * 3) + (4 * (5 + 6))"), 51);
-        assert_eq!(super::eval_2("2 * 3 + (4 * 5)"), 46);
-        assert_eq!(super::eval_2("5 + (8 * 3 + 9 + 3 * 4 * 3)"), 1445);
-        assert_eq!(
-            super::eval_2("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))"),
-            669060
-        );
+        assert_eq!(eval_2("1+2*3"), 9);
+        assert_eq!(eval_2("1*2+3"), 5);
+        assert_eq!(eval_2("1*2*3"), 6);
+        assert_eq!(eval_2("1+(2*3)+2"), 9);
+        assert_eq!(eval_2("((2+4*9) * (6+9*8+6) +6)"), 11664);
+        assert_eq!(eval_2("1 + (2 * 3) + (4 * (5 + 6))"), 51);
+        assert_eq!(eval_2("2 * 3 + (4 * 5)"), 46);
+        assert_eq!(eval_2("5 + (8 * 3 + 9 + 3 * 4 * 3)"), 1445);
+        assert_eq!(eval_2("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))"), 669060);
         assert_eq!(
-            super::eval_2("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2"),
+            eval_2("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2"),
             23340
         );
     }
@@ -1,15 +1,46 @@
+use crate::error::{Error, Result};
+use crate::helper::parse_day;
+use crate::solution::{Answer, Solution};
 use regex::Regex;
-use std::collections::HashMap;
-use std::fs;
+use std::collections::{HashMap, HashSet};
+
+pub struct Day19;
+
+impl Solution for Day19 {
+    const DAY: usize = 19;
+    type Input = (HashMap<usize, Rule>, Vec<String>);
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        // Everything after the rules is messages
+        let (rules, messages) = parse_day(
+            Self::DAY,
+            input,
+            nom::sequence::pair(parse_rules, nom::combinator::rest),
+        )?;
+        Ok((rules, messages.lines().map(|m| m.to_string()).collect()))
+    }
+
+    fn part1((rules, messages): &Self::Input) -> Result<Answer> {
+        run_1(rules, messages)
+            .map(Answer::from)
+            .ok_or(Error::NoSolution {
+                day: Self::DAY,
+                part: 1,
+            })
+    }
 
-pub fn run() {
-    let input = fs::read_to_string("day19.txt").unwrap();
-    println!("19:1 - {}", run_1(&input));
-    println!("19:2 - {}", run_2(&input));
+    fn part2((rules, messages): &Self::Input) -> Result<Answer> {
+        run_2(rules, messages)
+            .map(Answer::from)
+            .ok_or(Error::NoSolution {
+                day: Self::DAY,
+                part: 2,
+            })
+    }
 }
 
-#[derive(Debug)]
-enum Rule {
+#[derive(Clone, Debug)]
+pub enum Rule {
     SubRule(Vec<Vec<usize>>),
     Str(String),
 }
@@ -36,20 +67,41 @@ fn parse_str_rule(i: &str) -> nom::IResult<&str, (usize, Rule)> {
 }
 
 fn parse_rules(i: &str) -> nom::IResult<&str, HashMap<usize, Rule>> {
-    let rule_p = nom::branch::alt((parse_sub_rule, parse_str_rule));
+    // Each rule with the input from its line on, to point at it if it
+    // refers to a rule that isn't there
+    let rule_p = |i| {
+        let (rest, rule) = nom::branch::alt((parse_sub_rule, parse_str_rule))(i)?;
+        Ok((rest, (i, rule)))
+    };
     let (i, rules) = nom::multi::separated_list1(nom::character::complete::newline, rule_p)(i)?;
     let (i, _) = nom::multi::many_m_n(2, 2, nom::character::complete::newline)(i)?;
-    Ok((i, rules.into_iter().collect()))
+
+    let defined = rules
+        .iter()
+        .map(|(_, (num, _))| *num)
+        .collect::<HashSet<_>>();
+    for (at, (_, rule)) in &rules {
+        if let Rule::SubRule(sub_rules) = rule {
+            if sub_rules.iter().flatten().any(|r| !defined.contains(r)) {
+                let kind = nom::error::ErrorKind::Verify;
+                return Err(nom::Err::Failure(nom::error::Error::new(at, kind)));
+            }
+        }
+    }
+    Ok((i, rules.into_iter().map(|(_, rule)| rule).collect()))
 }
 
 const MAX_DEPTH: usize = 20;
 
-fn build_regexp(rule_num: usize, rules: &HashMap<usize, Rule>, depth: usize) -> String {
+/// The rule as a regex, or `None` if it isn't there. The parser made sure
+/// that rules only refer to ones that are, but rule 0 or those part 2
+/// needs may still be missing.
+fn build_regexp(rule_num: usize, rules: &HashMap<usize, Rule>, depth: usize) -> Option<String> {
     if depth == MAX_DEPTH {
-        return String::new();
+        return Some(String::new());
     }
-    let rule = rules.get(&rule_num).unwrap();
-    match rule {
+    let rule = rules.get(&rule_num)?;
+    let re = match rule {
         Rule::Str(s) => s.clone(),
         Rule::SubRule(sub_rules) => {
             let sr = sub_rules
@@ -57,10 +109,10 @@ fn build_regexp(rule_num: usize, rules: &HashMap<usize, Rule>, depth: usize) ->
                 .map(|sr| {
                     sr.iter()
                         .map(|r| build_regexp(*r, rules, depth + 1))
-                        .collect::<Vec<_>>()
-                        .join("")
+                        .collect::<Option<Vec<_>>>()
+                        .map(|sr| sr.join(""))
                 })
-                .collect::<Vec<_>>()
+                .collect::<Option<Vec<_>>>()?
                 .join("|");
             if sr.len() > 1 {
                 format!("({})", sr)
@@ -68,33 +120,37 @@ fn build_regexp(rule_num: usize, rules: &HashMap<usize, Rule>, depth: usize) ->
                 sr
             }
         }
-    }
+    };
+    Some(re)
 }
 
-fn run_1(input: &str) -> usize {
-    let (messages, rules) = parse_rules(input).unwrap();
-    let re = build_regexp(0, &rules, 0);
+fn run_1(rules: &HashMap<usize, Rule>, messages: &[String]) -> Option<usize> {
+    let re = build_regexp(0, rules, 0)?;
     let re = format!("^{}$", re);
 
-    let re = Regex::new(&re).unwrap();
+    // Too big a regex is no answer either
+    let re = Regex::new(&re).ok()?;
 
-    messages.lines().filter(|m| re.is_match(m)).count()
+    Some(messages.iter().filter(|m| re.is_match(m)).count())
 }
 
-fn run_2(input: &str) -> usize {
-    let (messages, mut rules) = parse_rules(input).unwrap();
+fn run_2(rules: &HashMap<usize, Rule>, messages: &[String]) -> Option<usize> {
+    let mut rules = rules.clone();
     rules.insert(8, Rule::SubRule(vec![vec![42], vec![42, 8]]));
     rules.insert(11, Rule::SubRule(vec![vec![42, 31], vec![42, 11, 31]]));
-    let re = build_regexp(0, &rules, 0);
+    let re = build_regexp(0, &rules, 0)?;
     let re = format!("^{}$", re);
 
-    let re = Regex::new(&re).unwrap();
+    // Too big a regex is no answer either
+    let re = Regex::new(&re).ok()?;
 
-    messages.lines().filter(|m| re.is_match(m)).count()
+    Some(messages.iter().filter(|m| re.is_match(m)).count())
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::solution::Solution;
+
     const INPUT_1: &str = r#"0: 4 1 5
 1: 2 3 | 3 2
 2: 4 4 | 5 5
@@ -128,7 +184,18 @@ aaaabbb"#;
 
     #[test]
     fn aoc19_run_1() {
-        assert_eq!(super::run_1(INPUT_1), 2);
+        let (rules, messages) = super::Day19::parse(INPUT_1).unwrap();
+        assert_eq!(super::run_1(&rules, &messages), Some(2));
+
+        // Rule 1 is missing
+        let error = super::Day19::parse("4: \"a\"\n0: 4 1\n\na").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 19, line 2, column 1: cannot parse \"0: 4 1\""
+        );
+        // Rule 0 is
+        let (rules, messages) = super::Day19::parse("4: \"a\"\n\na").unwrap();
+        assert_eq!(super::run_1(&rules, &messages), None);
     }
 
     #[test]
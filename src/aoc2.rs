@@ -1,4 +1,7 @@
+use crate::error::Result;
 use crate::helper;
+use crate::helper::parse_day;
+use crate::solution::{Answer, Solution};
 use nom::{
     bytes::complete::tag,
     character::complete::{alpha1, anychar, newline, space1},
@@ -7,10 +10,9 @@ use nom::{
     IResult,
 };
 use std::collections::HashMap;
-use std::fs;
 
 #[derive(Debug, Eq, PartialEq)]
-struct Policy {
+pub struct Policy {
     min: usize,
     max: usize,
     c: char,
@@ -26,21 +28,38 @@ fn parse_policy(i: &str) -> IResult<&str, Policy> {
 }
 
 fn parse_passwords(i: &str) -> IResult<&str, Vec<(Policy, &str)>> {
-    let password = separated_pair(|i| parse_policy(i), tag(": "), alpha1);
+    let password = separated_pair(parse_policy, tag(": "), alpha1);
     separated_list1(newline, password)(i)
 }
 
-pub fn run() {
-    let input = fs::read_to_string("day2.txt").unwrap();
-    println!("2:1: {}", run_1(&input));
-    println!("2:2: {}", run_2(&input));
+pub struct Day2;
+
+impl Solution for Day2 {
+    const DAY: usize = 2;
+    type Input = Vec<(Policy, String)>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let passwords = parse_day(Self::DAY, input, parse_passwords)?;
+        Ok(passwords
+            .into_iter()
+            .map(|(pol, pwd)| (pol, pwd.to_string()))
+            .collect())
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer> {
+        Ok(run_1(input).into())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer> {
+        Ok(run_2(input).into())
+    }
 }
 
 fn is_valid_1(pwd: &str, policy: &Policy) -> bool {
     let mut freq: HashMap<char, usize> = HashMap::new();
 
     pwd.chars().for_each(|c| {
-        let f = freq.entry(c).or_insert(0);
+        let f = freq.entry(c).or_default();
         *f += 1
     });
 
@@ -48,10 +67,9 @@ fn is_valid_1(pwd: &str, policy: &Policy) -> bool {
     *f >= policy.min && *f <= policy.max
 }
 
-fn run_1(input: &str) -> usize {
-    let (_, passwords) = parse_passwords(input).unwrap();
+fn run_1(passwords: &[(Policy, String)]) -> usize {
     passwords
-        .into_iter()
+        .iter()
         .filter(|(pol, pwd)| is_valid_1(pwd, pol))
         .count()
 }
@@ -63,23 +81,26 @@ fn is_valid_2(pwd: &str, policy: &Policy) -> bool {
     let range = (policy.max - 1)..(policy.max);
     let c2 = pwd.get(range).map(|c1| c1 == c_string);
 
-    match (c1, c2) {
-        (Some(true), None) | (Some(true), Some(false)) => true,
-        (Some(false), Some(true)) | (None, Some(true)) => true,
-        _ => false,
-    }
+    matches!(
+        (c1, c2),
+        (Some(true), None)
+            | (Some(true), Some(false))
+            | (Some(false), Some(true))
+            | (None, Some(true))
+    )
 }
 
-fn run_2(input: &str) -> usize {
-    let (_, passwords) = parse_passwords(input).unwrap();
+fn run_2(passwords: &[(Policy, String)]) -> usize {
     passwords
-        .into_iter()
+        .iter()
         .filter(|(pol, pwd)| is_valid_2(pwd, pol))
         .count()
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::solution::Solution;
+
     const INPUT: &str = "1-3 a: abcde
 1-3 b: cdefg
 2-9 c: ccccccccc";
@@ -110,13 +131,13 @@ mod tests {
 
     #[test]
     fn aoc2_run_1() {
-        use super::*;
-        assert_eq!(run_1(&INPUT), 2);
+        let input = super::Day2::parse(INPUT).unwrap();
+        assert_eq!(super::run_1(&input), 2);
     }
 
     #[test]
     fn aoc2_run_2() {
-        use super::*;
-        assert_eq!(run_2(&INPUT), 1);
+        let input = super::Day2::parse(INPUT).unwrap();
+        assert_eq!(super::run_2(&input), 1);
     }
 }
@@ -1,135 +1,500 @@
-use nom::{
-    branch::alt,
-    bytes::complete::tag,
-    character::complete::newline,
-    combinator::map,
-    multi::{many1, separated_list1},
-    IResult,
-};
-use std::collections::HashSet;
-use std::fs;
-
-#[derive(Debug, PartialEq, Eq, Hash)]
-struct Pos {
-    row: isize,
-    col: isize,
-}
-
-pub fn run() {
-    let input = fs::read_to_string("day20.txt").unwrap();
-    println!("20:1: {}", run_1(&input));
-    println!("20:2: {}", run_2(&input));
-}
-
-fn run_1(input: &str) -> usize {
-    let (_, all_directions) = parse(input).unwrap();
-
-    let mut tiles = HashSet::new();
-
-    for directions in all_directions.iter() {
-        let mut pos = Pos { row: 0, col: 0 };
-        for d in directions.iter() {
-            let even_row = pos.row % 2 == 0;
-            match d {
-                Direction::NW if even_row => {
-                    pos.row -= 1;
-                    pos.col -= 1;
-                }
-                Direction::NW => {
-                    pos.row -= 1;
-                }
-                Direction::NE if even_row => {
-                    pos.row -= 1;
-                }
-                Direction::NE => {
-                    pos.row -= 1;
-                    pos.col += 1;
-                }
-                Direction::SW if even_row => {
-                    pos.row += 1;
-                    pos.col -= 1;
-                }
-                Direction::SW => {
-                    pos.row += 1;
-                }
-                Direction::SE if even_row => {
-                    pos.row += 1;
-                }
-                Direction::SE => {
-                    pos.row += 1;
-                    pos.col += 1;
-                }
-                Direction::W => {
-                    pos.col -= 1;
-                }
-                Direction::E => {
-                    pos.col += 1;
-                }
-            }
+use crate::error::{Error, Result};
+use crate::helper::{Grid, Pos};
+use crate::solution::{Answer, Solution};
+use std::collections::HashMap;
+
+/// A square tile of the image and its id.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tile {
+    pub id: u64,
+    pub image: Grid<bool>,
+}
+
+pub struct Day20;
+
+impl Solution for Day20 {
+    const DAY: usize = 20;
+    type Input = Vec<Tile>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input).map_err(|e| e.for_day(Self::DAY))
+    }
+
+    fn part1(tiles: &Self::Input) -> Result<Answer> {
+        run_1(tiles).map(Answer::from).ok_or(Error::NoSolution {
+            day: Self::DAY,
+            part: 1,
+        })
+    }
+
+    fn part2(tiles: &Self::Input) -> Result<Answer> {
+        run_2(tiles).map(Answer::from).ok_or(Error::NoSolution {
+            day: Self::DAY,
+            part: 2,
+        })
+    }
+}
+
+/// Tiles are a `Tile <id>:` line followed by the rows of the image, with an
+/// empty line between tiles. All must be square and of the same size, at
+/// most 64 so that an edge fits in a `u64`.
+fn parse(input: &str) -> Result<Vec<Tile>> {
+    let mut tiles: Vec<Tile> = Vec::new();
+    // The line the current tile starts on, from 0
+    let mut start = 0;
+    for block in input.trim_end().split("\n\n") {
+        let (header, body) = block.split_once('\n').unwrap_or((block, ""));
+        let error = |line: usize, column: usize, text: &str| Error::Parse {
+            day: None,
+            line: start + line + 1,
+            column: column + 1,
+            text: text.to_string(),
+        };
+        let id = header
+            .strip_prefix("Tile ")
+            .and_then(|h| h.strip_suffix(':'))
+            .and_then(|id| id.parse().ok())
+            .ok_or_else(|| error(0, 0, header))?;
+        let image = Grid::parse(body, |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })
+        .map_err(|e| match e {
+            // The body starts on the line after the header
+            Error::Parse {
+                line, column, text, ..
+            } => error(line, column - 1, &text),
+            e => e,
+        })?;
+
+        let size = tiles.first().map_or(image.width(), |t| t.image.width());
+        if image.width() != image.height() || image.width() != size || !(1..=64).contains(&size) {
+            return Err(error(0, 0, header));
+        }
+        start += block.lines().count() + 1;
+        tiles.push(Tile { id, image });
+    }
+    Ok(tiles)
+}
+
+const TOP: usize = 0;
+const RIGHT: usize = 1;
+const BOTTOM: usize = 2;
+const LEFT: usize = 3;
+
+/// The cells at `positions` as the bits of a number, the first one highest.
+fn signature(image: &Grid<bool>, positions: impl Iterator<Item = Pos>) -> u64 {
+    positions.fold(0, |sig, pos| sig << 1 | image[pos] as u64)
+}
+
+/// A tile turned and mirrored one of the 8 ways, with the signatures of its
+/// top, right, bottom and left edges, read left to right and top to bottom.
+struct Oriented {
+    tile: usize,
+    image: Grid<bool>,
+    edges: [u64; 4],
+}
+
+impl Oriented {
+    fn new(tile: usize, image: Grid<bool>) -> Self {
+        let n = image.width();
+        let edges = [
+            signature(&image, (0..n).map(|x| (x, 0))),
+            signature(&image, (0..n).map(|y| (n - 1, y))),
+            signature(&image, (0..n).map(|x| (x, n - 1))),
+            signature(&image, (0..n).map(|y| (0, y))),
+        ];
+        Oriented { tile, image, edges }
+    }
+}
+
+/// Every tile in all 8 orientations, indexed by the signatures of their top
+/// and left edges. As any edge of a tile comes out on top read either way
+/// in some orientation, `by_top` has all edges there are.
+struct EdgeIndex {
+    oriented: Vec<Oriented>,
+    by_top: HashMap<u64, Vec<usize>>,
+    by_left: HashMap<u64, Vec<usize>>,
+}
+
+impl EdgeIndex {
+    fn new(tiles: &[Tile]) -> Self {
+        let oriented = tiles
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, tile)| {
+                let orientations = tile.image.orientations().into_iter();
+                orientations.map(move |image| Oriented::new(idx, image))
+            })
+            .collect::<Vec<_>>();
+        let mut by_top = HashMap::new();
+        let mut by_left = HashMap::new();
+        for (idx, o) in oriented.iter().enumerate() {
+            by_top
+                .entry(o.edges[TOP])
+                .or_insert_with(Vec::new)
+                .push(idx);
+            by_left
+                .entry(o.edges[LEFT])
+                .or_insert_with(Vec::new)
+                .push(idx);
+        }
+        EdgeIndex {
+            oriented,
+            by_top,
+            by_left,
+        }
+    }
+
+    /// Whether a tile other than `tile` has the edge `edge`.
+    fn matched(&self, tile: usize, edge: u64) -> bool {
+        let mut others = self.by_top.get(&edge).into_iter().flatten();
+        others.any(|o| self.oriented[*o].tile != tile)
+    }
+}
+
+/// Fills a square `size` tiles wide row by row, going back whenever no tile
+/// fits the next place.
+struct Assembler<'a> {
+    index: &'a EdgeIndex,
+    size: usize,
+    used: Vec<bool>,
+    placed: Vec<usize>,
+}
+
+impl Assembler<'_> {
+    fn place(&mut self) -> bool {
+        let k = self.placed.len();
+        if k == self.size * self.size {
+            return true;
         }
-        if tiles.contains(&pos) {
-            tiles.remove(&pos);
+        let (row, col) = (k / self.size, k % self.size);
+        let index = self.index;
+        let above = (row > 0).then(|| index.oriented[self.placed[k - self.size]].edges[BOTTOM]);
+        let candidates = if col > 0 {
+            let left = index.oriented[self.placed[k - 1]].edges[RIGHT];
+            index.by_left.get(&left).cloned().unwrap_or_default()
+        } else if let Some(above) = above {
+            index.by_top.get(&above).cloned().unwrap_or_default()
         } else {
-            tiles.insert(pos);
+            // A corner, with nothing to match above or to the left
+            let corners = (0..index.oriented.len()).filter(|o| {
+                let Oriented { tile, edges, .. } = index.oriented[*o];
+                !index.matched(tile, edges[TOP]) && !index.matched(tile, edges[LEFT])
+            });
+            corners.collect()
+        };
+
+        for o in candidates {
+            let oriented = &index.oriented[o];
+            if self.used[oriented.tile] || above.is_some_and(|edge| edge != oriented.edges[TOP]) {
+                continue;
+            }
+            self.used[oriented.tile] = true;
+            self.placed.push(o);
+            if self.place() {
+                return true;
+            }
+            self.placed.pop();
+            self.used[oriented.tile] = false;
         }
+        false
     }
-    tiles.len()
 }
 
-fn run_2(_input: &str) -> i64 {
-    unreachable!();
+/// The number of tiles along a side of a square of `count` of them.
+fn side(count: usize) -> Option<usize> {
+    (0..=count)
+        .find(|n| n * n >= count)
+        .filter(|n| n * n == count)
+}
+
+/// The tiles arranged into a square with all touching edges matching, row
+/// by row and each turned and mirrored as placed, if they can be.
+fn assemble(tiles: &[Tile]) -> Option<Vec<Tile>> {
+    let index = EdgeIndex::new(tiles);
+    let mut assembler = Assembler {
+        index: &index,
+        size: side(tiles.len()).filter(|n| *n > 0)?,
+        used: vec![false; tiles.len()],
+        placed: Vec::new(),
+    };
+    if !assembler.place() {
+        return None;
+    }
+    let placed = assembler.placed.into_iter().map(|o| {
+        let oriented = &index.oriented[o];
+        Tile {
+            id: tiles[oriented.tile].id,
+            image: oriented.image.clone(),
+        }
+    });
+    Some(placed.collect())
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-enum Direction {
-    NW,
-    NE,
-    W,
-    E,
-    SW,
-    SE,
+/// The arranged tiles as one picture, without their borders.
+fn picture(arranged: &[Tile]) -> Grid<bool> {
+    let size = side(arranged.len()).unwrap_or(0);
+    let inner = arranged
+        .first()
+        .map_or(0, |t| t.image.width().saturating_sub(2));
+    Grid::from_fn(size * inner, size * inner, |(x, y)| {
+        let tile = &arranged[y / inner * size + x / inner];
+        tile.image[(x % inner + 1, y % inner + 1)]
+    })
 }
 
-fn parse(i: &str) -> IResult<&str, Vec<Vec<Direction>>> {
-    let nw = map(tag("nw"), |_| Direction::NW);
-    let ne = map(tag("ne"), |_| Direction::NE);
-    let sw = map(tag("sw"), |_| Direction::SW);
-    let se = map(tag("se"), |_| Direction::SE);
-    let w = map(tag("w"), |_| Direction::W);
-    let e = map(tag("e"), |_| Direction::E);
+const SEA_MONSTER: [&str; 3] = [
+    "                  # ",
+    "#    ##    ##    ###",
+    " #  #  #  #  #  #   ",
+];
 
-    let line = many1(alt((nw, ne, sw, se, w, e)));
+/// The `#`s that aren't part of a sea monster, with the picture turned and
+/// mirrored the way that shows the most of them.
+fn roughness(picture: &Grid<bool>) -> usize {
+    let monster = SEA_MONSTER
+        .iter()
+        .enumerate()
+        .flat_map(|(y, row)| row.match_indices('#').map(move |(x, _)| (x, y)))
+        .collect::<Vec<_>>();
+    let (width, height) = (SEA_MONSTER[0].len(), SEA_MONSTER.len());
+    let total = picture.iter().filter(|(_, c)| **c).count();
 
-    separated_list1(newline, line)(i)
+    let monster_cells = |picture: &Grid<bool>| {
+        let mut seen = Grid::new(picture.width(), picture.height(), false);
+        for y in 0..(picture.height() + 1).saturating_sub(height) {
+            for x in 0..(picture.width() + 1).saturating_sub(width) {
+                if monster.iter().all(|(dx, dy)| picture[(x + dx, y + dy)]) {
+                    monster
+                        .iter()
+                        .for_each(|(dx, dy)| seen[(x + dx, y + dy)] = true);
+                }
+            }
+        }
+        seen.iter().filter(|(_, c)| **c).count()
+    };
+    let most = picture.orientations().iter().map(monster_cells).max();
+    total - most.unwrap_or(0)
+}
+
+fn run_1(tiles: &[Tile]) -> Option<u64> {
+    let arranged = assemble(tiles)?;
+    let size = side(arranged.len())?;
+    let corners = [0, size - 1, size * (size - 1), size * size - 1];
+    Some(corners.iter().map(|idx| arranged[*idx].id).product())
+}
+
+fn run_2(tiles: &[Tile]) -> Option<usize> {
+    assemble(tiles).map(|arranged| roughness(&picture(&arranged)))
 }
 
 #[cfg(test)]
 mod tests {
+    use super::Tile;
+    use crate::helper::{Grid, Rng};
+    use crate::solution::Solution;
+
+    const INPUT: &str = "Tile 2311:
+..##.#..#.
+##..#.....
+#...##..#.
+####.#...#
+##.##.###.
+##...#.###
+.#.#.#..##
+..#....#..
+###...#.#.
+..###..###
+
+Tile 1951:
+#.##...##.
+#.####...#
+.....#..##
+#...######
+.##.#....#
+.###.#####
+###.##.##.
+.###....#.
+..#.#..#.#
+#...##.#..
+
+Tile 1171:
+####...##.
+#..##.#..#
+##.#..#.#.
+.###.####.
+..###.####
+.##....##.
+.#...####.
+#.##.####.
+####..#...
+.....##...
+
+Tile 1427:
+###.##.#..
+.#..#.##..
+.#.##.#..#
+#.#.#.##.#
+....#...##
+...##..##.
+...#.#####
+.#.####.#.
+..#..###.#
+..##.#..#.
+
+Tile 1489:
+##.#.#....
+..##...#..
+.##..##...
+..#...#...
+#####...#.
+#..#.#.#.#
+...#.#.#..
+##.#...##.
+..##.##.##
+###.##.#..
+
+Tile 2473:
+#....####.
+#..#.##...
+#.##..#...
+######.#.#
+.#...#.#.#
+.#########
+.###.#..#.
+########.#
+##...##.#.
+..###.#.#.
+
+Tile 2971:
+..#.#....#
+#...###...
+#.#.###...
+##.##..#..
+.#####..##
+.#..####.#
+#..#.#..#.
+..####.###
+..#.#.###.
+...#.#.#.#
+
+Tile 2729:
+...#.#.#.#
+####.#....
+..#.#.....
+....#..#.#
+.##..##.#.
+.#.####...
+####.#.#..
+##.####...
+##..#.##..
+#.##...##.
+
+Tile 3079:
+#.#.#####.
+.#..######
+..#.......
+######....
+####.#..#.
+.#...#.##.
+#.#####.##
+..#.###...
+..#.......
+..#.###...";
 
     #[test]
     fn aoc20_run_1() {
-        let ans = super::run_1(
-            "sesenwnenenewseeswwswswwnenewsewsw
-neeenesenwnwwswnenewnwwsewnenwseswesw
-seswneswswsenwwnwse
-nwnwneseeswswnenewneswwnewseswneseene
-swweswneswnenwsewnwneneseenw
-eesenwseswswnenwswnwnwsewwnwsene
-sewnenenenesenwsewnenwwwse
-wenwwweseeeweswwwnwwe
-wsweesenenewnwwnwsenewsenwwsesesenwne
-neeswseenwwswnwswswnw
-nenwswwsewswnenenewsenwsenwnesesenew
-enewnwewneswsewnwswenweswnenwsenwsw
-sweneswneswneneenwnewenewwneswswnese
-swwesenesewenwneswnwwneseswwne
-enesenwswwswneneswsenwnewswseenwsese
-wnwnesenesenenwwnenwsewesewsesesew
-nenewswnwewswnenesenwnesewesw
-eneswnwswnwsenenwnwnwwseeswneewsenese
-neswnwewnwnwseenwseesewsenwsweewe
-wseweeenwnesenwwwswnew",
+        let tiles = super::Day20::parse(INPUT).unwrap();
+        assert_eq!(super::run_1(&tiles), Some(20899048083289));
+    }
+
+    #[test]
+    fn aoc20_run_2() {
+        let tiles = super::Day20::parse(INPUT).unwrap();
+        assert_eq!(super::run_2(&tiles), Some(273));
+    }
+
+    /// A random picture with sea monsters at `monsters`, cut into `size` by
+    /// `size` tiles of 10 by 10 that are turned, mirrored and shuffled at
+    /// random. Returns the tiles as puzzle input, the product of the corner
+    /// ids and the roughness.
+    fn jigsaw(rng: &mut Rng, size: usize, monsters: &[(usize, usize)]) -> (String, u64, usize) {
+        // Neighbouring tiles overlap by a row or column, their borders
+        let full = size * 9 + 1;
+        let mut full = Grid::from_fn(full, full, |(x, y)| {
+            let border = x % 9 == 0 || y % 9 == 0;
+            // Sparse inside, so that no other monsters turn up
+            rng.below(if border { 2 } else { 5 }) == 0
+        });
+        let inner = |i: usize| i / 8 * 9 + 1 + i % 8;
+        for (x, y) in monsters {
+            for (dy, row) in super::SEA_MONSTER.iter().enumerate() {
+                for (dx, _) in row.match_indices('#') {
+                    full[(inner(x + dx), inner(y + dy))] = true;
+                }
+            }
+        }
+        let picture = Grid::from_fn(size * 8, size * 8, |(x, y)| full[(inner(x), inner(y))]);
+        let total = picture.iter().filter(|(_, c)| **c).count();
+
+        let mut tiles = Vec::new();
+        for row in 0..size {
+            for col in 0..size {
+                let image = Grid::from_fn(10, 10, |(x, y)| full[(col * 9 + x, row * 9 + y)]);
+                let id = 1000 + 7 * tiles.len() as u64;
+                let image = image.orientations().swap_remove(rng.below(8) as usize);
+                tiles.push(Tile { id, image });
+            }
+        }
+        let corners = [0, size - 1, size * (size - 1), size * size - 1];
+        let product = corners.iter().map(|idx| tiles[*idx].id).product();
+        for idx in (1..tiles.len()).rev() {
+            tiles.swap(idx, rng.below(idx as u64 + 1) as usize);
+        }
+
+        let input = tiles
+            .iter()
+            .map(|tile| {
+                let image = tile.image.map(|c| if *c { '#' } else { '.' });
+                format!("Tile {}:\n{}\n", tile.id, image)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        (input, product, total - 15 * monsters.len())
+    }
+
+    #[test]
+    fn aoc20_assemble() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        let puzzles = [(3, vec![(1, 2), (3, 16)]), (4, vec![(10, 27)]), (1, vec![])];
+        for (size, monsters) in puzzles.iter() {
+            let (input, product, roughness) = jigsaw(&mut rng, *size, monsters);
+            let tiles = super::Day20::parse(&input).unwrap();
+            assert_eq!(tiles.len(), size * size);
+            assert_eq!(super::run_1(&tiles), Some(product));
+            assert_eq!(super::run_2(&tiles), Some(roughness));
+        }
+    }
+
+    #[test]
+    fn aoc20_parse_error() {
+        let error = |input: &str| super::Day20::parse(input).unwrap_err().to_string();
+        assert_eq!(
+            error("Tile 1:\n#.\n.#\n\nTile x:\n#.\n.#"),
+            "day 20, line 5, column 1: cannot parse \"Tile x:\""
+        );
+        assert_eq!(
+            error("Tile 1:\n#.\n.#\n\nTile 2:\n#.\n.?"),
+            "day 20, line 7, column 2: cannot parse \".?\""
+        );
+        // Not the same size as the first
+        assert_eq!(
+            error("Tile 1:\n#.\n.#\n\nTile 2:\n#.#\n.#.\n..."),
+            "day 20, line 5, column 1: cannot parse \"Tile 2:\""
         );
-        assert_eq!(ans, 10);
+        let tiles = super::Day20::parse("Tile 1:\n#.\n.#\n\nTile 2:\n#.\n.#").unwrap();
+        assert_eq!(super::run_1(&tiles), None);
     }
 }
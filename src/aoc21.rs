@@ -1,3 +1,6 @@
+use crate::error::{Error, Result};
+use crate::helper::parse_day;
+use crate::solution::{Answer, Solution};
 use nom::{
     bytes::complete::tag,
     character::complete::{alpha1, newline, space1},
@@ -5,36 +8,68 @@ use nom::{
     IResult,
 };
 use std::collections::{HashMap, HashSet};
-use std::fs;
 
-pub fn run() {
-    let input = fs::read_to_string("day21.txt").unwrap();
-    println!("21:1 {:}", run_1(&input));
-    println!("21:2 {:?}", run_2(&input));
-}
+type Food = (HashSet<String>, Vec<String>);
+type Candidates<'a> = Vec<(&'a str, HashSet<&'a str>)>;
 
-fn run_1(input: &str) -> usize {
-    let (_, foods) = parse(input).unwrap();
+pub struct Day21;
 
-    let mut all_ingredients_count: HashMap<&str, usize> = HashMap::new();
+impl Solution for Day21 {
+    const DAY: usize = 21;
+    type Input = Vec<Food>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let foods = parse_day(Self::DAY, input, parse)?;
+        Ok(foods
+            .into_iter()
+            .map(|(ingredients, alergens)| {
+                (
+                    ingredients.into_iter().map(|i| i.to_string()).collect(),
+                    alergens.into_iter().map(|a| a.to_string()).collect(),
+                )
+            })
+            .collect())
+    }
+
+    fn part1(foods: &Self::Input) -> Result<Answer> {
+        Ok(run_1(foods).into())
+    }
+
+    fn part2(foods: &Self::Input) -> Result<Answer> {
+        run_2(foods).map(Answer::from).ok_or(Error::NoSolution {
+            day: Self::DAY,
+            part: 2,
+        })
+    }
+}
 
+fn alergens_to_ingredients(foods: &[Food]) -> HashMap<&str, HashSet<&str>> {
     let mut alergens_to_ingredients: HashMap<&str, HashSet<&str>> = HashMap::new();
     for (ingredients, alergens) in foods.iter() {
-        // Insert count of the ingredients
-        ingredients.iter().for_each(|ing| {
-            *all_ingredients_count.entry(*ing).or_insert(0) += 1;
-        });
-
+        let ingredients: HashSet<&str> = ingredients.iter().map(|i| i.as_str()).collect();
         for alergen in alergens {
-            if let Some(previous) = alergens_to_ingredients.get_mut(alergen) {
-                let in_both: HashSet<&str> =
-                    previous.intersection(ingredients).map(|v| *v).collect();
+            if let Some(previous) = alergens_to_ingredients.get_mut(alergen.as_str()) {
+                let in_both: HashSet<&str> = previous.intersection(&ingredients).copied().collect();
                 *previous = in_both;
             } else {
-                alergens_to_ingredients.insert(alergen.clone(), ingredients.clone());
+                alergens_to_ingredients.insert(alergen, ingredients.clone());
             }
         }
     }
+    alergens_to_ingredients
+}
+
+fn run_1(foods: &[Food]) -> usize {
+    let mut all_ingredients_count: HashMap<&str, usize> = HashMap::new();
+
+    for (ingredients, _) in foods.iter() {
+        // Insert count of the ingredients
+        ingredients.iter().for_each(|ing| {
+            *all_ingredients_count.entry(ing).or_default() += 1;
+        });
+    }
+
+    let alergens_to_ingredients = alergens_to_ingredients(foods);
 
     // Filter out all ingredients that has know alergens
     all_ingredients_count
@@ -48,33 +83,32 @@ fn run_1(input: &str) -> usize {
         .sum()
 }
 
-fn run_2(input: &str) -> String {
-    let (_, foods) = parse(input).unwrap();
-
-    let mut alergens_to_ingredients: HashMap<&str, HashSet<&str>> = HashMap::new();
-    for (ingredients, alergens) in foods.iter() {
-        for alergen in alergens {
-            if let Some(previous) = alergens_to_ingredients.get_mut(alergen) {
-                let in_both: HashSet<&str> =
-                    previous.intersection(ingredients).map(|v| *v).collect();
-                *previous = in_both;
-            } else {
-                alergens_to_ingredients.insert(alergen.clone(), ingredients.clone());
-            }
-        }
-    }
+/// The dangerous ingredients sorted by their alergen, or `None` if some
+/// alergen can't be narrowed down to a single ingredient.
+fn run_2(foods: &[Food]) -> Option<String> {
+    let mut alergens_to_ingredients = alergens_to_ingredients(foods);
 
     let mut new_alergens_to_ingredients: HashMap<&str, &str> = HashMap::new();
     while !alergens_to_ingredients.is_empty() {
-        let (one_ingredients, mut multi_ingredients): (
-            Vec<(&str, HashSet<&str>)>,
-            Vec<(&str, HashSet<&str>)>,
-        ) = alergens_to_ingredients
-            .into_iter()
-            .partition(|(_, ing)| ing.len() == 1);
+        crate::runner::checkpoint();
+        if alergens_to_ingredients.values().any(HashSet::is_empty) {
+            return None;
+        }
+        let (one_ingredients, mut multi_ingredients): (Candidates, Candidates) =
+            alergens_to_ingredients
+                .into_iter()
+                .partition(|(_, ing)| ing.len() == 1);
+        // Nothing left to narrow the others down with
+        if one_ingredients.is_empty() {
+            return None;
+        }
 
         for (a, i) in one_ingredients {
-            let ing = *i.iter().next().unwrap();
+            let ing = i.into_iter().next()?;
+            // Two alergens in the same ingredient
+            if new_alergens_to_ingredients.values().any(|i| *i == ing) {
+                return None;
+            }
             new_alergens_to_ingredients.insert(a, ing);
             for (_, ings) in multi_ingredients.iter_mut() {
                 ings.remove(ing);
@@ -83,14 +117,14 @@ fn run_2(input: &str) -> String {
         alergens_to_ingredients = multi_ingredients.into_iter().collect();
     }
 
-    let mut sorted_alergens: Vec<&str> = new_alergens_to_ingredients.keys().map(|k| *k).collect();
-    sorted_alergens.sort();
+    let mut sorted_alergens: Vec<&str> = new_alergens_to_ingredients.keys().copied().collect();
+    sorted_alergens.sort_unstable();
 
-    sorted_alergens
+    let dangerous = sorted_alergens
         .into_iter()
-        .map(|a| *new_alergens_to_ingredients.get(a).unwrap())
-        .collect::<Vec<&str>>()
-        .join(",")
+        .map(|a| new_alergens_to_ingredients[a])
+        .collect::<Vec<&str>>();
+    Some(dangerous.join(","))
 }
 
 fn parse_food(i: &str) -> IResult<&str, (HashSet<&str>, Vec<&str>)> {
@@ -101,12 +135,15 @@ fn parse_food(i: &str) -> IResult<&str, (HashSet<&str>, Vec<&str>)> {
     Ok((i, (ingredients.into_iter().collect(), alergens)))
 }
 
+#[allow(clippy::type_complexity)]
 fn parse(i: &str) -> IResult<&str, Vec<(HashSet<&str>, Vec<&str>)>> {
     separated_list1(newline, parse_food)(i)
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::solution::Solution;
+
     const INPUT_1: &str = "mxmxvkd kfcds sqjhc nhms (contains dairy, fish)
 trh fvjkl sbzzf mxmxvkd (contains dairy)
 sqjhc fvjkl (contains soy)
@@ -114,17 +151,30 @@ sqjhc mxmxvkd sbzzf (contains fish)";
 
     #[test]
     fn aoc21_parse() {
-        let (i, foods) = super::parse(INPUT_1).unwrap();
+        let (_, foods) = super::parse(INPUT_1).unwrap();
         assert_eq!(foods.len(), 4);
         assert_eq!(foods[0].0.len(), 4);
         assert_eq!(foods[0].1.len(), 2);
     }
     #[test]
     fn aoc21_run_1() {
-        assert_eq!(super::run_1(INPUT_1), 5);
+        let foods = super::Day21::parse(INPUT_1).unwrap();
+        assert_eq!(super::run_1(&foods), 5);
     }
     #[test]
     fn aoc21_run_2() {
-        assert_eq!(&super::run_2(INPUT_1), "mxmxvkd,sqjhc,fvjkl");
+        let foods = super::Day21::parse(INPUT_1).unwrap();
+        assert_eq!(super::run_2(&foods).as_deref(), Some("mxmxvkd,sqjhc,fvjkl"));
+    }
+
+    #[test]
+    fn aoc21_unsolvable() {
+        let run_2 = |input| super::run_2(&super::Day21::parse(input).unwrap());
+        // No ingredient in both
+        assert_eq!(run_2("a b (contains x)\nc d (contains x)"), None);
+        // Either could have either
+        assert_eq!(run_2("a b (contains x, y)"), None);
+        // Both alergens in the one ingredient
+        assert_eq!(run_2("a (contains x, y)"), None);
     }
 }
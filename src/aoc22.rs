@@ -1,18 +1,33 @@
+use crate::error::{Error, Result};
+use crate::helper::parse_day;
+use crate::solution::{Answer, Solution};
 use nom::{
-    branch::alt,
-    bytes::complete::tag,
     character::complete::newline,
     multi::{many_m_n, separated_list1},
     sequence::separated_pair,
     IResult,
 };
-use std::collections::{HashSet, VecDeque};
-use std::fs;
+use std::collections::VecDeque;
 
-pub fn run() {
-    let input = fs::read_to_string("day22.txt").unwrap();
-    println!("22:1 {}", run_1(&input));
-    println!("22:2 {}", run_2(&input));
+type Decks = (VecDeque<usize>, VecDeque<usize>);
+
+pub struct Day22;
+
+impl Solution for Day22 {
+    const DAY: usize = 22;
+    type Input = Decks;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_day(Self::DAY, input, parse)
+    }
+
+    fn part1(decks: &Self::Input) -> Result<Answer> {
+        Ok(run_1(decks).into())
+    }
+
+    fn part2(decks: &Self::Input) -> Result<Answer> {
+        run_2(decks).map(Answer::from)
+    }
 }
 
 fn parse_player(i: &str) -> IResult<&str, Vec<usize>> {
@@ -22,15 +37,16 @@ fn parse_player(i: &str) -> IResult<&str, Vec<usize>> {
     separated_list1(newline, crate::helper::uval)(i)
 }
 
-fn parse(i: &str) -> IResult<&str, (VecDeque<usize>, VecDeque<usize>)> {
+fn parse(i: &str) -> IResult<&str, Decks> {
     let (i, (p1, p2)) = separated_pair(parse_player, many_m_n(2, 2, newline), parse_player)(i)?;
     Ok((i, (p1.into_iter().collect(), p2.into_iter().collect())))
 }
 
-fn run_1(input: &str) -> usize {
-    let (_, (mut p1, mut p2)) = parse(input).unwrap();
+fn run_1(decks: &Decks) -> usize {
+    let (mut p1, mut p2) = decks.clone();
 
     while !p1.is_empty() && !p2.is_empty() {
+        crate::runner::checkpoint();
         let p1_card = p1.pop_front().unwrap();
         let p2_card = p2.pop_front().unwrap();
         if p1_card > p2_card {
@@ -49,34 +65,15 @@ fn run_1(input: &str) -> usize {
     winner.into_iter().zip(1..).map(|(a, b)| a * b).sum()
 }
 
-fn run_2(input: &str) -> usize {
-    let (_, (mut p1, mut p2)) = parse(input).unwrap();
-    let mut game = 1;
-
-    loop {
-        let mut p1_won = false;
-        let mut p1_previous_hands = HashSet::new();
-        let mut p2_previous_hands = HashSet::new();
-        println!("=== Game {} ===\n", game);
-        for round in 1.. {
-            println!("-- Round {} (Game {}) --\n", round, game);
-            println!("Player 1's deck: {:?}", p1);
-            println!("Player 2's deck: {:?}", p2);
-            if p1_previous_hands.contains(&p1) || p2_previous_hands.contains(&p2) {
-                p1_won = true;
-                break;
-            }
-
-            p1_previous_hands.insert(p1.clone());
-            p2_previous_hands.insert(p2.clone());
-        }
-        break;
-    }
-    todo!()
+/// Recursive combat isn't solved yet.
+fn run_2(_decks: &Decks) -> Result<usize> {
+    Err(Error::NotImplemented { day: 22, part: 2 })
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::solution::Solution;
+
     const INPUT_1: &str = "Player 1:
 9
 2
@@ -93,9 +90,11 @@ Player 2:
 
     #[test]
     fn aoc22_run_1() {
-        assert_eq!(super::run_1(INPUT_1), 306);
+        let decks = super::Day22::parse(INPUT_1).unwrap();
+        assert_eq!(super::run_1(&decks), 306);
     }
 
+    #[allow(dead_code)]
     const INPUT_2: &str = "Player 1:
 43
 19
@@ -107,6 +106,10 @@ Player 2:
 
     #[test]
     fn aoc22_run_2() {
-        // assert_eq!(super::run_2(INPUT_1), 291);
+        let decks = super::Day22::parse(INPUT_1).unwrap();
+        assert!(matches!(
+            super::run_2(&decks),
+            Err(crate::error::Error::NotImplemented { day: 22, part: 2 })
+        ));
     }
 }
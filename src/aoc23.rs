@@ -1,15 +1,27 @@
-use std::fs;
+use crate::error::{Error, Result};
+use crate::solution::{Answer, Solution};
 
-pub fn run() {
-    let input = fs::read_to_string("day23.txt").unwrap();
-    println!("23:1 {}", run_1(&input));
-    println!("23:2 {}", run_2(&input));
-}
+pub struct Day23;
 
-fn run_1(_program: &str) -> i64 {
-    todo!()
-}
+impl Solution for Day23 {
+    const DAY: usize = 23;
+    type Input = String;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(input.to_string())
+    }
+
+    fn part1(_program: &Self::Input) -> Result<Answer> {
+        Err(Error::NotImplemented {
+            day: Self::DAY,
+            part: 1,
+        })
+    }
 
-fn run_2(_program: &str) -> i64 {
-    todo!()
+    fn part2(_program: &Self::Input) -> Result<Answer> {
+        Err(Error::NotImplemented {
+            day: Self::DAY,
+            part: 2,
+        })
+    }
 }
@@ -1,208 +1,71 @@
-use nom::{
-    branch::alt,
-    bytes::complete::tag,
-    character::complete::newline,
-    combinator::map,
-    multi::{many1, separated_list1},
-    IResult,
-};
+use crate::automaton::{Automaton, Hex, Rule};
+use crate::error::Result;
+use crate::helper::parse_day;
+use crate::hex::{self, Cube, Direction};
+use crate::solution::{Answer, Solution};
+use nom::{character::complete::newline, multi::separated_list1, IResult};
 use std::collections::HashSet;
-use std::fs;
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
-struct Pos {
-    row: isize,
-    col: isize,
-}
+pub struct Day24;
 
-pub fn run() {
-    let input = fs::read_to_string("day24.txt").unwrap();
-    println!("24:1: {}", run_1(&input));
-    println!("24:2: {}", run_2(&input));
-}
+impl Solution for Day24 {
+    const DAY: usize = 24;
+    type Input = Vec<Vec<Direction>>;
 
-fn follow_directions(input: &str) -> HashSet<Pos> {
-    let (_, all_directions) = parse(input).unwrap();
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_day(Self::DAY, input, parse)
+    }
 
+    fn part1(all_directions: &Self::Input) -> Result<Answer> {
+        Ok(run_1(all_directions).into())
+    }
+
+    fn part2(all_directions: &Self::Input) -> Result<Answer> {
+        Ok(run_2(all_directions).into())
+    }
+}
+
+fn follow_directions(all_directions: &[Vec<Direction>]) -> HashSet<Cube> {
     let mut tiles = HashSet::new();
 
     for directions in all_directions.iter() {
-        let mut pos = Pos { row: 0, col: 0 };
-        for d in directions.iter() {
-            let even_row = pos.row % 2 == 0;
-            match d {
-                Direction::NW if even_row => {
-                    pos.row -= 1;
-                    pos.col -= 1;
-                }
-                Direction::NW => {
-                    pos.row -= 1;
-                }
-                Direction::NE if even_row => {
-                    pos.row -= 1;
-                }
-                Direction::NE => {
-                    pos.row -= 1;
-                    pos.col += 1;
-                }
-                Direction::SW if even_row => {
-                    pos.row += 1;
-                    pos.col -= 1;
-                }
-                Direction::SW => {
-                    pos.row += 1;
-                }
-                Direction::SE if even_row => {
-                    pos.row += 1;
-                }
-                Direction::SE => {
-                    pos.row += 1;
-                    pos.col += 1;
-                }
-                Direction::W => {
-                    pos.col -= 1;
-                }
-                Direction::E => {
-                    pos.col += 1;
-                }
-            }
-        }
-        if tiles.contains(&pos) {
-            tiles.remove(&pos);
-        } else {
+        let pos = Cube::ORIGIN.walk(directions);
+        if !tiles.remove(&pos) {
             tiles.insert(pos);
         }
     }
     tiles
 }
 
-fn run_1(input: &str) -> usize {
-    let tiles = follow_directions(input);
+fn run_1(all_directions: &[Vec<Direction>]) -> usize {
+    let tiles = follow_directions(all_directions);
     tiles.len()
 }
 
-fn neighbors(pos: &Pos) -> Vec<Pos> {
-    if pos.row % 2 == 0 {
-        // Even row
-        vec![
-            Pos {
-                col: pos.col - 1,
-                row: pos.row - 1,
-            },
-            Pos {
-                col: pos.col,
-                row: pos.row - 1,
-            },
-            Pos {
-                col: pos.col - 1,
-                row: pos.row + 1,
-            },
-            Pos {
-                col: pos.col,
-                row: pos.row + 1,
-            },
-            Pos {
-                col: pos.col - 1,
-                row: pos.row,
-            },
-            Pos {
-                col: pos.col + 1,
-                row: pos.row,
-            },
-        ]
-    } else {
-        vec![
-            Pos {
-                col: pos.col,
-                row: pos.row - 1,
-            },
-            Pos {
-                col: pos.col + 1,
-                row: pos.row - 1,
-            },
-            Pos {
-                col: pos.col,
-                row: pos.row + 1,
-            },
-            Pos {
-                col: pos.col + 1,
-                row: pos.row + 1,
-            },
-            Pos {
-                col: pos.col - 1,
-                row: pos.row,
-            },
-            Pos {
-                col: pos.col + 1,
-                row: pos.row,
-            },
-        ]
-    }
-}
-
-fn run_2(input: &str) -> usize {
-    let mut tiles = follow_directions(input);
-
-    for i in 1..=100 {
-        let mut new_tiles = HashSet::new();
-
-        for pos in tiles.iter() {
-            let nbrs = neighbors(pos);
-            let (black_nbrs, white_nbrs): (Vec<&Pos>, Vec<&Pos>) =
-                nbrs.iter().partition(|p| tiles.contains(p));
-
-            // We know that pos is black
-            // any black tile with zero or more than 2 black tiles
-            // immediately adjacent to it is flipped to white.
-            // i.e. if it has one or two neighbors, it survives
-            if black_nbrs.len() == 1 || black_nbrs.len() == 2 {
-                new_tiles.insert((*pos).clone());
-            }
-
-            for white_nbr in white_nbrs.into_iter() {
-                let num_black_nbrs = neighbors(white_nbr)
-                    .iter()
-                    .filter(|p| tiles.contains(p))
-                    .count();
-                if num_black_nbrs == 2 {
-                    new_tiles.insert(white_nbr.clone());
-                }
-            }
-        }
-
-        tiles = new_tiles;
-    }
-    tiles.len()
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-enum Direction {
-    NW,
-    NE,
-    W,
-    E,
-    SW,
-    SE,
+fn run_2(all_directions: &[Vec<Direction>]) -> usize {
+    let black = follow_directions(all_directions);
+    // A black tile with zero or more than 2 black neighbours is flipped to
+    // white, a white one with exactly 2 black neighbours to black
+    let rule = Rule {
+        birth: 2..=2,
+        survive: 1..=2,
+    };
+    let mut automaton = Automaton::new(Hex, rule, black);
+    automaton.run(Some(100));
+    automaton.live().len()
 }
 
 fn parse(i: &str) -> IResult<&str, Vec<Vec<Direction>>> {
-    let nw = map(tag("nw"), |_| Direction::NW);
-    let ne = map(tag("ne"), |_| Direction::NE);
-    let sw = map(tag("sw"), |_| Direction::SW);
-    let se = map(tag("se"), |_| Direction::SE);
-    let w = map(tag("w"), |_| Direction::W);
-    let e = map(tag("e"), |_| Direction::E);
-
-    let line = many1(alt((nw, ne, sw, se, w, e)));
-
-    separated_list1(newline, line)(i)
+    separated_list1(newline, hex::directions)(i)
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::solution::Solution;
+
     #[test]
     fn aoc24_run_1() {
-        let ans = super::run_1(
+        let all_directions = super::Day24::parse(
             "sesenwnenenewseeswwswswwnenewsewsw
 neeenesenwnwwswnenewnwwsewnenwseswesw
 seswneswswsenwwnwse
@@ -223,13 +86,14 @@ nenewswnwewswnenesenwnesewesw
 eneswnwswnwsenenwnwnwwseeswneewsenese
 neswnwewnwnwseenwseesewsenwsweewe
 wseweeenwnesenwwwswnew",
-        );
-        assert_eq!(ans, 10);
+        )
+        .unwrap();
+        assert_eq!(super::run_1(&all_directions), 10);
     }
 
     #[test]
     fn aoc24_run_2() {
-        let ans = super::run_2(
+        let all_directions = super::Day24::parse(
             "sesenwnenenewseeswwswswwnenewsewsw
 neeenesenwnwwswnenewnwwsewnenwseswesw
 seswneswswsenwwnwse
@@ -250,7 +114,8 @@ nenewswnwewswnenesenwnesewesw
 eneswnwswnwsenenwnwnwwseeswneewsenese
 neswnwewnwnwseenwseesewsenwsweewe
 wseweeenwnesenwwwswnew",
-        );
-        assert_eq!(ans, 2208);
+        )
+        .unwrap();
+        assert_eq!(super::run_2(&all_directions), 2208);
     }
 }
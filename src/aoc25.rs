@@ -1,9 +1,34 @@
-use std::collections::HashMap;
+use crate::error::{Error, Result};
+use crate::helper::{parse_day, uval};
+use crate::solution::{Answer, Solution};
+use nom::{character::complete::newline, sequence::separated_pair};
 
 const SUB_DIV: usize = 20201227;
 
-pub fn run() {
-    println!("25:1 {}", run_1(17773298, 15530095));
+pub struct Day25;
+
+impl Solution for Day25 {
+    const DAY: usize = 25;
+    // The card's and the door's public keys
+    type Input = (usize, usize);
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_day(Self::DAY, input, separated_pair(uval, newline, uval))
+    }
+
+    fn part1(&(card_pk, door_pk): &Self::Input) -> Result<Answer> {
+        run_1(card_pk, door_pk)
+            .map(Answer::from)
+            .ok_or(Error::NoSolution {
+                day: Self::DAY,
+                part: 1,
+            })
+    }
+
+    fn part2(_: &Self::Input) -> Result<Answer> {
+        // There's no second puzzle on the last day
+        Ok(Answer::None)
+    }
 }
 
 fn transform_subject_number(subject_number: usize, loop_size: usize) -> usize {
@@ -14,27 +39,27 @@ fn transform_subject_number(subject_number: usize, loop_size: usize) -> usize {
     value
 }
 
-fn run_1(card_pk: usize, door_pk: usize) -> usize {
+fn run_1(card_pk: usize, door_pk: usize) -> Option<usize> {
     let subject_key = 7;
-    // let mut lookup = HashMap::new();
-    for loop_size in 1.. {
-        let transformed = transform_subject_number(subject_key, loop_size);
-        // lookup.insert(loop_size, transformed);
+    // Keep the running value instead of transforming from scratch for
+    // every loop size
+    let mut transformed = 1;
+    // The transformed values repeat after at most SUB_DIV loops
+    for loop_size in 1..SUB_DIV {
+        transformed = (transformed * subject_key) % SUB_DIV;
         if transformed == card_pk {
-            println!("Found card");
-            return transform_subject_number(door_pk, loop_size);
+            return Some(transform_subject_number(door_pk, loop_size));
         } else if transformed == door_pk {
-            println!("Found door");
-            return transform_subject_number(card_pk, loop_size);
+            return Some(transform_subject_number(card_pk, loop_size));
         }
     }
-    unreachable!()
+    None
 }
 
 #[cfg(test)]
 mod tests {
     #[test]
     fn aoc25_run_1() {
-        assert_eq!(super::run_1(5764801, 17807724), 14897079);
+        assert_eq!(super::run_1(5764801, 17807724), Some(14897079));
     }
 }
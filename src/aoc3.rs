@@ -1,21 +1,34 @@
-use std::fs;
+use crate::error::Result;
+use crate::helper::Grid;
+use crate::solution::{Answer, Solution};
 
-type Map = Vec<Vec<char>>;
+/// Where the trees are.
+type Map = Grid<bool>;
 
-pub fn run() {
-    let input = fs::read_to_string("day3.txt").unwrap();
-    let map = parse(&input);
+pub struct Day3;
 
-    println!("3:1 - {}", run_1(&map));
-    println!("3:2 - {}", run_2(&map));
+impl Solution for Day3 {
+    const DAY: usize = 3;
+    type Input = Map;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse(input).map_err(|e| e.for_day(Self::DAY))
+    }
+
+    fn part1(map: &Self::Input) -> Result<Answer> {
+        Ok(run_1(map).into())
+    }
+
+    fn part2(map: &Self::Input) -> Result<Answer> {
+        Ok(run_2(map).into())
+    }
 }
 
-fn count_trees(map: &Map, slope: (usize, usize)) -> usize {
+fn count_trees(map: &Map, slope: (isize, isize)) -> usize {
     let mut pos = (0, 0);
     let mut tree_count = 0;
-    let width = map[0].len();
-    while pos.1 < map.len() {
-        if map[pos.1][pos.0 % width] == '#' {
+    while pos.1 < map.height() as isize {
+        if *map.get_wrapping(pos) {
             tree_count += 1;
         }
         pos.0 += slope.0;
@@ -33,20 +46,16 @@ fn run_2(map: &Map) -> usize {
 
     slopes
         .iter()
-        .fold(1, |acc, slope| acc * count_trees(map, *slope))
+        .map(|slope| count_trees(map, *slope))
+        .product()
 }
 
-fn parse(input: &str) -> Map {
-    let mut res = Vec::new();
-    for line in input.lines() {
-        if line.is_empty() {
-            break;
-        }
-        let row = line.chars().collect();
-        res.push(row);
-    }
-
-    res
+fn parse(input: &str) -> Result<Map> {
+    Grid::parse(input, |c| match c {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None,
+    })
 }
 
 #[cfg(test)]
@@ -65,20 +74,21 @@ mod tests {
 
     #[test]
     fn aoc3_parse() {
-        let map = super::parse(INPUT);
-        assert_eq!(11, map.len());
-        assert_eq!(11, map[2].len());
+        let map = super::parse(INPUT).unwrap();
+        assert_eq!(11, map.height());
+        assert_eq!(11, map.width());
+        assert!(super::parse("..#\n.o.").is_err());
     }
 
     #[test]
     fn aoc3_run_1() {
-        let map = super::parse(INPUT);
+        let map = super::parse(INPUT).unwrap();
         assert_eq!(super::run_1(&map), 7);
     }
 
     #[test]
     fn aoc3_run_2() {
-        let map = super::parse(INPUT);
+        let map = super::parse(INPUT).unwrap();
         assert_eq!(super::run_2(&map), 336);
     }
 }
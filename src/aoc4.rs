@@ -1,3 +1,6 @@
+use crate::error::Result;
+use crate::helper::parse_day;
+use crate::solution::{Answer, Solution};
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_till1},
@@ -7,20 +10,42 @@ use nom::{
     IResult,
 };
 use std::collections::HashMap;
-use std::fs;
 
-pub fn run() {
-    let input = fs::read_to_string("day4.txt").unwrap();
-    println!("4:1 {}", run_1(&input));
-    println!("4:2 {}", run_2(&input));
+type Passport = Vec<(String, String)>;
+
+pub struct Day4;
+
+impl Solution for Day4 {
+    const DAY: usize = 4;
+    type Input = Vec<Passport>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let passports = parse_day(Self::DAY, input, parse_passports)?;
+        Ok(passports
+            .into_iter()
+            .map(|passport| {
+                passport
+                    .into_iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect()
+            })
+            .collect())
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer> {
+        Ok(run_1(input).into())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer> {
+        Ok(run_2(input).into())
+    }
 }
 
-fn run_1(input: &str) -> usize {
-    let pp = parse_passports(input).unwrap().1;
+fn run_1(pp: &[Passport]) -> usize {
     let required = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"];
     pp.iter()
         .filter(|passport| {
-            let keys: Vec<&str> = passport.iter().map(|(k, _)| *k).collect();
+            let keys: Vec<&str> = passport.iter().map(|(k, _)| k.as_str()).collect();
             required.iter().all(|req| keys.contains(req))
         })
         .count()
@@ -45,10 +70,7 @@ fn validate_field(key: &str, value: &str) -> bool {
         "hgt" => {
             let cm = regex::Regex::new("^([0-9]+)cm$").unwrap();
             let inch = regex::Regex::new("^([0-9]+)in$").unwrap();
-            match (
-                cm.captures_iter(value).next(),
-                inch.captures_iter(value).next(),
-            ) {
+            match (cm.captures(value), inch.captures(value)) {
                 (Some(c), _) => val_range(c.get(1).unwrap().as_str(), 3, 150, 193),
                 (_, Some(c)) => val_range(c.get(1).unwrap().as_str(), 2, 59, 76),
                 _ => false,
@@ -64,19 +86,19 @@ fn validate_field(key: &str, value: &str) -> bool {
             re.is_match(value)
         }
         "cid" => true,
-        _ => unreachable!(),
+        // The parser only lets the keys above through
+        _ => false,
     }
 }
 
-fn run_2(input: &str) -> usize {
-    let pp = parse_passports(input).unwrap().1;
+fn run_2(pp: &[Passport]) -> usize {
     let required = ["byr", "iyr", "eyr", "hgt", "hcl", "ecl", "pid"];
     let valids = pp
         .iter()
         .filter_map(|passport| {
             let passport = passport
                 .iter()
-                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .cloned()
                 .collect::<HashMap<String, String>>();
             if required.iter().all(|r| passport.contains_key(*r)) {
                 Some(passport)
@@ -89,14 +111,25 @@ fn run_2(input: &str) -> usize {
 }
 
 fn parse_val(i: &str) -> IResult<&str, &str> {
-    take_till1(|c| match c {
-        ':' | ' ' | '\n' => true,
-        _ => false,
-    })(i)
+    take_till1(|c| matches!(c, ':' | ' ' | '\n'))(i)
+}
+
+fn parse_key(i: &str) -> IResult<&str, &str> {
+    let keys = (
+        tag("byr"),
+        tag("iyr"),
+        tag("eyr"),
+        tag("hgt"),
+        tag("hcl"),
+        tag("ecl"),
+        tag("pid"),
+        tag("cid"),
+    );
+    alt(keys)(i)
 }
 
 fn parse_kv(i: &str) -> IResult<&str, (&str, &str)> {
-    separated_pair(parse_val, tag(":"), parse_val)(i)
+    separated_pair(parse_key, tag(":"), parse_val)(i)
 }
 
 fn parse_passport(i: &str) -> IResult<&str, Vec<(&str, &str)>> {
@@ -109,6 +142,7 @@ fn parse_passports(i: &str) -> IResult<&str, Vec<Vec<(&str, &str)>>> {
 
 #[cfg(test)]
 mod tests {
+    use crate::solution::Solution;
 
     const INPUT: &str = "ecl:gry pid:860033327 eyr:2020 hcl:#fffffd
 byr:1937 iyr:2017 cid:147 hgt:183cm
@@ -138,19 +172,26 @@ iyr:2011 ecl:brn hgt:59in";
         .unwrap();
         assert_eq!(passport.len(), 8);
 
-        let input = "a:b
-c:d
+        let input = "byr:b
+cid:d
 
-e:f";
-        let (i, passports) = super::parse_passports(&input).unwrap();
+pid:f";
+        let (i, passports) = super::parse_passports(input).unwrap();
         assert_eq!(i, "");
         assert_eq!(passports.len(), 2);
         let (_, passports) = super::parse_passports(INPUT).unwrap();
         assert_eq!(passports.len(), 4);
+
+        let error = super::Day4::parse("ecl:gry\npid:860033327 foo:1").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "day 4, line 2, column 14: cannot parse \"pid:860033327 foo:1\""
+        );
     }
     #[test]
     fn aoc4_run_1() {
-        assert_eq!(super::run_1(INPUT), 2);
+        let input = super::Day4::parse(INPUT).unwrap();
+        assert_eq!(super::run_1(&input), 2);
     }
     const INVALID: &str = "eyr:1972 cid:100
 hcl:#18171d ecl:amb hgt:170 pid:186cm iyr:2018 byr:1926
@@ -205,7 +246,9 @@ iyr:2010 hgt:158cm hcl:#b6652a ecl:blu byr:1944 eyr:2021 pid:093154719";
 
     #[test]
     fn aoc4_run_2() {
-        assert_eq!(super::run_2(INVALID), 0);
-        assert_eq!(super::run_2(VALID), 4);
+        let input = super::Day4::parse(INVALID).unwrap();
+        assert_eq!(super::run_2(&input), 0);
+        let input = super::Day4::parse(VALID).unwrap();
+        assert_eq!(super::run_2(&input), 4);
     }
 }
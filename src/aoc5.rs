@@ -1,117 +1,118 @@
-use std::fs;
+use crate::error::{Error, Result};
+use crate::helper::parse_day;
+use crate::solution::{Answer, Solution};
+use nom::{
+    character::complete::{newline, one_of},
+    combinator::map,
+    multi::{count, separated_list1},
+    sequence::pair,
+    IResult,
+};
 
 #[derive(Debug)]
-enum Cmd {
+pub enum Cmd {
     Front,
     Back,
     Left,
     Right,
 }
 
-impl std::convert::From<char> for Cmd {
-    fn from(c: char) -> Self {
-        match c {
-            'F' => Self::Front,
-            'B' => Self::Back,
-            'L' => Self::Left,
-            'R' => Self::Right,
-            _ => unreachable!(),
-        }
-    }
-}
-
-fn parse_cmds(i: &str) -> Vec<Vec<Cmd>> {
-    i.lines()
-        .map(|line| {
-            line.chars()
-                .map(std::convert::Into::into)
-                .collect::<Vec<_>>()
-        })
-        .collect()
+fn parse_cmds(i: &str) -> IResult<&str, Vec<Vec<Cmd>>> {
+    let row = map(
+        one_of("FB"),
+        |c| if c == 'F' { Cmd::Front } else { Cmd::Back },
+    );
+    let seat = map(
+        one_of("LR"),
+        |c| if c == 'L' { Cmd::Left } else { Cmd::Right },
+    );
+    let pass = map(pair(count(row, 7), count(seat, 3)), |(mut row, seat)| {
+        row.extend(seat);
+        row
+    });
+    separated_list1(newline, pass)(i)
 }
 
-fn boarding_pass(p: &[Cmd]) -> (usize, usize) {
-    let mut top_row = 128;
-    let mut low_row = 0;
-    assert_eq!(p.len(), 10);
-    for cmd in &p[0..7] {
-        // dbg! {(cmd, low_row, top_row)};
+/// Halves `0..size` by each of `cmds`, front and left keeping the lower
+/// half, back and right the upper one.
+fn bisect(cmds: &[Cmd], size: usize) -> usize {
+    let (mut low, mut high) = (0, size);
+    for cmd in cmds {
         match cmd {
-            Cmd::Front => {
-                top_row -= (top_row - low_row) / 2;
-            }
-            Cmd::Back => {
-                low_row += (top_row - low_row) / 2;
-            }
-            _ => unreachable!(),
-        }
-    }
-    let mut max_seat = 8;
-    let mut min_seat = 0;
-    for cmd in &p[7..10] {
-        // dbg! {(cmd, min_seat, max_seat)};
-        match cmd {
-            Cmd::Right => {
-                min_seat += (max_seat - min_seat) / 2;
-            }
-            Cmd::Left => {
-                max_seat -= (max_seat - min_seat) / 2;
-            }
-            _ => unreachable!(),
+            Cmd::Front | Cmd::Left => high -= (high - low) / 2,
+            Cmd::Back | Cmd::Right => low += (high - low) / 2,
         }
     }
+    low
+}
 
-    // dbg! {(low_row, min_seat)}
-    (low_row, min_seat)
+/// The row and seat of a pass, which the parser made sure has 7 row and
+/// then 3 seat commands.
+fn boarding_pass(p: &[Cmd]) -> (usize, usize) {
+    (bisect(&p[0..7], 128), bisect(&p[7..10], 8))
 }
 
-pub fn run() {
-    let input = fs::read_to_string("day5.txt").unwrap();
+pub struct Day5;
+
+impl Solution for Day5 {
+    const DAY: usize = 5;
+    type Input = Vec<Vec<Cmd>>;
 
-    println!("5:1 {}", run_1(&input));
-    println!("5:2 {}", run_2(&input));
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_day(Self::DAY, input, parse_cmds)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer> {
+        Ok(run_1(input).into())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer> {
+        run_2(input).map(Answer::from).ok_or(Error::NoSolution {
+            day: Self::DAY,
+            part: 2,
+        })
+    }
 }
 
 fn seat_id((row, seat): (usize, usize)) -> usize {
     row * 8 + seat
 }
 
-fn run_1(input: &str) -> usize {
-    let bps = parse_cmds(input);
-
+fn run_1(bps: &[Vec<Cmd>]) -> usize {
     bps.iter()
         .map(|bp| boarding_pass(bp))
-        .map(|rs| seat_id(rs))
+        .map(seat_id)
         .max()
         .unwrap()
 }
 
-fn run_2(input: &str) -> usize {
-    let bps = parse_cmds(input);
-
+fn run_2(bps: &[Vec<Cmd>]) -> Option<usize> {
     let taken: Vec<usize> = bps
         .iter()
         .map(|bp| boarding_pass(bp))
-        .map(|rs| seat_id(rs))
+        .map(seat_id)
         .collect();
-    let first_seat_id: usize = 1 * 8 + 0;
+    let first_seat_id: usize = 8;
     let last_seat_id: usize = 126 * 8 + 7;
-    (first_seat_id..last_seat_id)
-        .find(|seat_id| {
-            taken.contains(&(seat_id - 1))
-                && !taken.contains(seat_id)
-                && taken.contains(&(seat_id + 1))
-        })
-        .unwrap()
+    (first_seat_id..last_seat_id).find(|seat_id| {
+        taken.contains(&(seat_id - 1)) && !taken.contains(seat_id) && taken.contains(&(seat_id + 1))
+    })
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::solution::Solution;
+
     #[test]
     fn aoc5_run_1() {
-        assert_eq!(super::run_1("FBFBBFFRLR"), 357);
-        assert_eq!(super::run_1("BFFFBBFRRR"), 567);
-        assert_eq!(super::run_1("FFFBBBFRRR"), 119);
-        assert_eq!(super::run_1("BBFFBBFRLL"), 820);
+        let run_1 = |i| super::run_1(&super::Day5::parse(i).unwrap());
+        assert_eq!(run_1("FBFBBFFRLR"), 357);
+        assert_eq!(run_1("BFFFBBFRRR"), 567);
+        assert_eq!(run_1("FFFBBBFRRR"), 119);
+        assert_eq!(run_1("BBFFBBFRLL"), 820);
+        assert!(super::Day5::parse("FBFBBFFRLX").is_err());
+        // Seat commands where the row goes, and the other way round
+        assert!(super::Day5::parse("LLLLLLLFFF").is_err());
+        assert!(super::Day5::parse("FBFBBFFRLB").is_err());
     }
 }
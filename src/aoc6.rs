@@ -1,14 +1,29 @@
+use crate::error::Result;
+use crate::helper::parse_day;
+use crate::solution::{Answer, Solution};
 use nom::bytes::complete::tag;
 use nom::character::complete::{alpha1, newline};
 use nom::multi::separated_list1;
 use nom::IResult;
 use std::collections::HashSet;
-use std::fs;
 
-pub fn run() {
-    let input = fs::read_to_string("day6.txt").unwrap();
-    println!("6:1: {}", run_1(&input));
-    println!("6:2: {}", run_2(&input));
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: usize = 6;
+    type Input = Vec<Group>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_day(Self::DAY, input, groups)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer> {
+        Ok(run_1(input).into())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer> {
+        Ok(run_2(input).into())
+    }
 }
 
 type Person = HashSet<char>;
@@ -16,7 +31,7 @@ type Group = Vec<Person>;
 
 fn person(i: &str) -> IResult<&str, HashSet<char>> {
     let (i, v) = alpha1(i)?;
-    Ok((i, v.chars().into_iter().collect()))
+    Ok((i, v.chars().collect()))
 }
 
 fn group(i: &str) -> IResult<&str, Vec<Person>> {
@@ -27,36 +42,32 @@ fn groups(i: &str) -> IResult<&str, Vec<Group>> {
     separated_list1(tag("\n\n"), group)(i)
 }
 
-fn run_1(input: &str) -> usize {
-    let (_, groups) = groups(input).unwrap();
-
+fn run_1(groups: &[Group]) -> usize {
     // fold each group into its own
     groups
-        .into_iter()
+        .iter()
         .map(|group| {
             group
-                .into_iter()
+                .iter()
                 .fold(HashSet::new(), |totals, person| {
-                    totals.union(&person).map(|c| *c).collect()
+                    totals.union(person).copied().collect()
                 })
                 .len()
         })
         .sum()
 }
 
-fn run_2(input: &str) -> usize {
-    let (_, groups) = groups(input).unwrap();
-
+fn run_2(groups: &[Group]) -> usize {
     let all_possibles: HashSet<char> = ('a'..='z').collect();
 
     // fold each group into its own
     groups
-        .into_iter()
+        .iter()
         .map(|group| {
             group
-                .into_iter()
+                .iter()
                 .fold(all_possibles.clone(), |totals, person| {
-                    totals.intersection(&person).map(|c| *c).collect()
+                    totals.intersection(person).copied().collect()
                 })
                 .len()
         })
@@ -65,6 +76,8 @@ fn run_2(input: &str) -> usize {
 
 #[cfg(test)]
 mod tests {
+    use crate::solution::Solution;
+
     const INPUT: &str = "abc
 
 a
@@ -90,11 +103,13 @@ b";
 
     #[test]
     fn aoc6_run_1() {
-        assert_eq!(super::run_1(INPUT), 11);
+        let input = super::Day6::parse(INPUT).unwrap();
+        assert_eq!(super::run_1(&input), 11);
     }
 
     #[test]
     fn aoc6_run_2() {
-        assert_eq!(super::run_2(INPUT), 6);
+        let input = super::Day6::parse(INPUT).unwrap();
+        assert_eq!(super::run_2(&input), 6);
     }
 }
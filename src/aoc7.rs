@@ -1,3 +1,6 @@
+use crate::error::{Error, Result};
+use crate::helper::parse_day;
+use crate::solution::{Answer, Solution};
 use nom::{
     branch::alt,
     bytes::complete::tag,
@@ -8,18 +11,36 @@ use nom::{
     IResult,
 };
 use std::collections::{HashMap, HashSet};
-use std::fs;
 
-pub fn run() {
-    let input = fs::read_to_string("day7.txt").unwrap();
-    println!("day7-1: {}", run_1(&input));
-    println!("day7-2: {}", run_2(&input));
-}
+type Bags = HashMap<String, Vec<Content>>;
+
+pub struct Day7;
 
-pub fn run_1(input: &str) -> usize {
-    let (_, bags) = bags(input).unwrap();
-    let bags: HashMap<&str, Vec<Content>> = bags.into_iter().collect();
+impl Solution for Day7 {
+    const DAY: usize = 7;
+    type Input = Bags;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let bags = parse_day(Self::DAY, input, bags)?;
+        Ok(bags
+            .into_iter()
+            .map(|(name, content)| (name.to_string(), content))
+            .collect())
+    }
 
+    fn part1(input: &Self::Input) -> Result<Answer> {
+        Ok(run_1(input).into())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer> {
+        run_2(input).map(Answer::from).ok_or(Error::NoSolution {
+            day: Self::DAY,
+            part: 2,
+        })
+    }
+}
+
+pub fn run_1(bags: &Bags) -> usize {
     let mut can_contain: HashSet<String> = HashSet::new();
     let mut searched: HashSet<String> = HashSet::new();
 
@@ -30,18 +51,15 @@ pub fn run_1(input: &str) -> usize {
 
         for s in to_search_for {
             searched.insert(s.to_string());
-            let bags = bags.iter().filter(|(_name, contents)| {
-                contents
-                    .iter()
-                    .find(|Content { bag, .. }| bag == s)
-                    .is_some()
-            });
+            let bags = bags
+                .iter()
+                .filter(|(_name, contents)| contents.iter().any(|Content { bag, .. }| bag == s));
             bags.clone().for_each(|(name, _)| {
                 can_contain.insert(name.to_string());
             });
 
-            bags.filter(|(name, _)| !searched.contains(**name))
-                .for_each(|(name, _)| new_to_search_for.push(&name));
+            bags.filter(|(name, _)| !searched.contains(*name))
+                .for_each(|(name, _)| new_to_search_for.push(name));
         }
 
         to_search_for = new_to_search_for;
@@ -50,37 +68,42 @@ pub fn run_1(input: &str) -> usize {
     can_contain.len()
 }
 
+/// The number of bags in `name`, itself included, or `None` if a bag in it
+/// isn't defined or ends up containing itself.
 fn count_bags(
     name: &str,
-    bags: &HashMap<&str, Vec<Content>>,
+    bags: &Bags,
     cache: &mut HashMap<String, usize>,
-) -> usize {
+    open: &mut HashSet<String>,
+) -> Option<usize> {
     if let Some(v) = cache.get(name) {
-        return *v;
+        return Some(*v);
+    }
+    if !open.insert(name.to_string()) {
+        return None;
     }
 
-    let sub_bags = bags.get(name).unwrap();
+    let sub_bags = bags.get(name)?;
 
-    let count = 1 + sub_bags
-        .iter()
-        .map(|Content { bag, quantity }| quantity * count_bags(bag, bags, cache))
-        .sum::<usize>();
+    let mut count = 1;
+    for Content { bag, quantity } in sub_bags {
+        count += quantity * count_bags(bag, bags, cache, open)?;
+    }
+    open.remove(name);
     cache.insert(name.to_string(), count);
-    count
+    Some(count)
 }
 
-pub fn run_2(input: &str) -> usize {
-    let (_, bags) = bags(input).unwrap();
-    let bags: HashMap<&str, Vec<Content>> = bags.into_iter().collect();
+pub fn run_2(bags: &Bags) -> Option<usize> {
     let mut cache: HashMap<String, usize> = HashMap::new();
 
-    let ans = count_bags("shiny gold", &bags, &mut cache);
+    let ans = count_bags("shiny gold", bags, &mut cache, &mut HashSet::new())?;
 
     // subtract one as we will count the shiny gold bag in count_bags
-    ans - 1
+    Some(ans - 1)
 }
 
-struct Content {
+pub struct Content {
     bag: String,
     quantity: usize,
 }
@@ -125,6 +148,8 @@ fn bags(i: &str) -> IResult<&str, Vec<(&str, Vec<Content>)>> {
 
 #[cfg(test)]
 mod tests {
+    use crate::solution::Solution;
+
     const INPUT: &str = "light red bags contain 1 bright white bag, 2 muted yellow bags.
 dark orange bags contain 3 bright white bags, 4 muted yellow bags.
 bright white bags contain 1 shiny gold bag.
@@ -156,12 +181,22 @@ dark violet bags contain no other bags.";
     }
     #[test]
     fn aoc7_run_1() {
-        assert_eq!(super::run_1(INPUT), 4);
+        let input = super::Day7::parse(INPUT).unwrap();
+        assert_eq!(super::run_1(&input), 4);
     }
 
     #[test]
     fn aoc7_run_2() {
-        assert_eq!(super::run_2(INPUT), 32);
-        assert_eq!(super::run_2(INPUT_2), 126);
+        let input = super::Day7::parse(INPUT).unwrap();
+        assert_eq!(super::run_2(&input), Some(32));
+        let input = super::Day7::parse(INPUT_2).unwrap();
+        assert_eq!(super::run_2(&input), Some(126));
+
+        // Dark red is never defined, and then contains itself
+        let input = super::Day7::parse("shiny gold bags contain 2 dark red bags.").unwrap();
+        assert_eq!(super::run_2(&input), None);
+        let input = "shiny gold bags contain 2 dark red bags.
+dark red bags contain 1 dark red bag.";
+        assert_eq!(super::run_2(&super::Day7::parse(input).unwrap()), None);
     }
 }
@@ -1,67 +1,57 @@
-use std::fs;
+use crate::error::{Error, Result};
+use crate::intcode::{Termination, CPU};
+use crate::solution::{Answer, Solution};
 
-pub fn run() {
-    let input = fs::read_to_string("day8.txt").unwrap();
-    println!("day8-1: {}", run_1(&input));
-    println!("day8-2: {}", run_2(&input));
-}
+pub struct Day8;
+
+impl Solution for Day8 {
+    const DAY: usize = 8;
+    type Input = CPU;
 
-fn run_1(input: &str) -> i64 {
-    let mut cpu = crate::intcode::CPU::new(input);
-    let mut visited_pcs = std::collections::HashSet::new();
-    while !visited_pcs.contains(&cpu.pc) {
-        // dbg!(cpu.pc);
-        visited_pcs.insert(cpu.pc);
-        cpu.step();
-        // dbg!(cpu.pc);
+    fn parse(input: &str) -> Result<Self::Input> {
+        CPU::new(input).map_err(|e| e.for_day(Self::DAY))
     }
-    cpu.acc
-}
 
-fn run_2(input: &str) -> i64 {
-    let orig_cpu = crate::intcode::CPU::new(input);
+    fn part1(cpu: &Self::Input) -> Result<Answer> {
+        run_1(cpu).map(Answer::from).ok_or(Error::NoSolution {
+            day: Self::DAY,
+            part: 1,
+        })
+    }
 
-    for (pc, op) in orig_cpu.code.iter().enumerate() {
-        let new_op = match op {
-            crate::intcode::Op::Jmp(v) => Some(crate::intcode::Op::Nop(*v)),
-            crate::intcode::Op::Nop(v) => Some(crate::intcode::Op::Jmp(*v)),
-            _ => None,
-        };
-        if let Some(new_op) = new_op {
-            let mut cpu = orig_cpu.clone();
-            cpu.code[pc] = new_op;
-            let mut visited_pcs = std::collections::HashSet::new();
-            while !visited_pcs.contains(&cpu.pc) {
-                // dbg!(cpu.pc);
-                visited_pcs.insert(cpu.pc);
-                if cpu.step().is_none() {
-                    return cpu.acc;
-                }
-            }
-        }
+    fn part2(cpu: &Self::Input) -> Result<Answer> {
+        run_2(cpu).map(Answer::from).ok_or(Error::NoSolution {
+            day: Self::DAY,
+            part: 2,
+        })
     }
-    panic!("Didn't solve")
+}
+
+fn run_1(cpu: &CPU) -> Option<i64> {
+    match cpu.clone().run() {
+        Termination::InfiniteLoop { acc, .. } => Some(acc),
+        _ => None,
+    }
+}
+
+fn run_2(cpu: &CPU) -> Option<i64> {
+    crate::analysis::terminating_flip(&cpu.code).map(|patch| patch.acc)
 }
 
 #[cfg(test)]
 mod tests {
-    const INPUT: &str = "nop +0
-acc +1
-jmp +4
-acc +3
-jmp -3
-acc -99
-acc +1
-jmp -4
-acc +6";
+    use crate::intcode::EXAMPLE;
+    use crate::solution::Solution;
 
     #[test]
     fn aoc8_run_1() {
-        assert_eq!(super::run_1(INPUT), 5);
+        let cpu = super::Day8::parse(EXAMPLE).unwrap();
+        assert_eq!(super::run_1(&cpu), Some(5));
     }
 
     #[test]
     fn aoc8_run_2() {
-        assert_eq!(super::run_2(INPUT), 8);
+        let cpu = super::Day8::parse(EXAMPLE).unwrap();
+        assert_eq!(super::run_2(&cpu), Some(8));
     }
 }
@@ -1,9 +1,36 @@
-use std::fs;
+use crate::error::{Error, Result};
+use crate::helper::parse_day;
+use crate::solution::{Answer, Solution};
 
-pub fn run() {
-    let input = fs::read_to_string("day9.txt").unwrap();
-    println!("day9-1: {}", run_1(&input, 100));
-    println!("day9-2: {}", run_2(&input, 100));
+const PREAMBLE_LEN: usize = 100;
+
+pub struct Day9;
+
+impl Solution for Day9 {
+    const DAY: usize = 9;
+    type Input = Vec<usize>;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_day(Self::DAY, input, parse_xmas)
+    }
+
+    fn part1(xmas: &Self::Input) -> Result<Answer> {
+        run_1(xmas, PREAMBLE_LEN)
+            .map(Answer::from)
+            .ok_or(Error::NoSolution {
+                day: Self::DAY,
+                part: 1,
+            })
+    }
+
+    fn part2(xmas: &Self::Input) -> Result<Answer> {
+        run_2(xmas, PREAMBLE_LEN)
+            .map(Answer::from)
+            .ok_or(Error::NoSolution {
+                day: Self::DAY,
+                part: 2,
+            })
+    }
 }
 
 fn is_valid(v: usize, preamble: &[usize]) -> bool {
@@ -15,27 +42,23 @@ fn is_valid(v: usize, preamble: &[usize]) -> bool {
             }
         }
     }
-    return false;
+    false
 }
 
-fn run_1(input: &str, preamble_len: usize) -> usize {
-    let (_, xmas) = parse_xmas(input).unwrap();
-    let mut start = 0;
-    loop {
-        let end = start + preamble_len;
-        let preamble = &xmas[start..end];
-        // dbg! {(xmas[end], preamble)};
-        if !is_valid(xmas[end], preamble) {
-            return xmas[end];
+fn run_1(xmas: &[usize], preamble_len: usize) -> Option<usize> {
+    xmas.windows(preamble_len + 1).find_map(|window| {
+        let (preamble, v) = window.split_at(preamble_len);
+        // dbg! {(v[0], preamble)};
+        if is_valid(v[0], preamble) {
+            None
+        } else {
+            Some(v[0])
         }
-
-        start += 1;
-    }
+    })
 }
 
-fn run_2(input: &str, preamble_len: usize) -> usize {
-    let (_, xmas) = parse_xmas(input).unwrap();
-    let invalid = run_1(input, preamble_len);
+fn run_2(xmas: &[usize], preamble_len: usize) -> Option<usize> {
+    let invalid = run_1(xmas, preamble_len)?;
 
     for (i, v1) in xmas.iter().enumerate() {
         let mut min = v1;
@@ -49,11 +72,11 @@ fn run_2(input: &str, preamble_len: usize) -> usize {
                 break;
             }
             if sum == invalid {
-                return min + max;
+                return Some(min + max);
             }
         }
     }
-    unreachable!()
+    None
 }
 
 fn parse_xmas(i: &str) -> nom::IResult<&str, Vec<usize>> {
@@ -65,6 +88,8 @@ fn parse_xmas(i: &str) -> nom::IResult<&str, Vec<usize>> {
 
 #[cfg(test)]
 mod tests {
+    use crate::solution::Solution;
+
     const INPUT: &str = "35
 20
 15
@@ -93,11 +118,13 @@ mod tests {
 
     #[test]
     fn aoc9_run_1() {
-        assert_eq!(super::run_1(INPUT, 5), 127);
+        let xmas = super::Day9::parse(INPUT).unwrap();
+        assert_eq!(super::run_1(&xmas, 5), Some(127));
     }
 
     #[test]
     fn aoc9_run_2() {
-        assert_eq!(super::run_2(INPUT, 5), 62);
+        let xmas = super::Day9::parse(INPUT).unwrap();
+        assert_eq!(super::run_2(&xmas, 5), Some(62));
     }
 }
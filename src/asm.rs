@@ -0,0 +1,290 @@
+//! Text listings of handheld console programs. `disassemble` prints one
+//! instruction per line with its address and, for jumps, the address it
+//! lands on:
+//!
+//! ```text
+//! 0: nop +0
+//! 1: acc +1
+//! 2: jmp +4  ; -> 6
+//! ```
+//!
+//! `assemble` reads that back, as well as the plain puzzle format. Lines
+//! may start with labels (`loop:`), which `jmp` and `nop` take in place of
+//! an offset (`jmp loop`). Addresses and `;` comments are ignored. Intcode
+//! instructions are read in the form `Op` prints them, e.g.
+//! `add 1, [rb-2], [7]`, so any code a CPU can hold survives the trip.
+
+use crate::error::{Error, Result};
+use crate::intcode::{Op, Param};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::Write;
+
+fn is_label(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Where a relative jump from `pc` lands, if anywhere in `code` or right
+/// after it.
+fn target(code: &[Op], pc: usize, offset: i64) -> Option<usize> {
+    let target = (pc as i64).checked_add(offset)?;
+    usize::try_from(target).ok().filter(|t| *t <= code.len())
+}
+
+/// An Intcode operand as `Param` prints it: `5`, `[5]` or `[rb+5]`.
+fn param(s: &str) -> Option<Param> {
+    match s.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+        Some(inner) => match inner.strip_prefix("rb") {
+            Some(offset) => offset.parse().ok().map(Param::Relative),
+            None => inner.parse().ok().map(Param::Position),
+        },
+        None => s.parse().ok().map(Param::Immediate),
+    }
+}
+
+/// The Intcode instruction `mnemonic` with `params`, or `None` if it takes
+/// a different number of them.
+fn intcode(mnemonic: &str, params: &[Param]) -> Option<Op> {
+    Some(match (mnemonic, params) {
+        ("add", [a, b, c]) => Op::Add(*a, *b, *c),
+        ("mul", [a, b, c]) => Op::Mul(*a, *b, *c),
+        ("store", [a]) => Op::Store(*a),
+        ("load", [a]) => Op::Load(*a),
+        ("jt", [a, b]) => Op::JumpIfTrue(*a, *b),
+        ("jf", [a, b]) => Op::JumpIfFalse(*a, *b),
+        ("lt", [a, b, c]) => Op::LT(*a, *b, *c),
+        ("eq", [a, b, c]) => Op::Eq(*a, *b, *c),
+        ("arb", [a]) => Op::AdjRelBase(*a),
+        ("end", []) => Op::End,
+        _ => return None,
+    })
+}
+
+fn listing(code: &[Op], labels: &HashMap<usize, String>) -> String {
+    let width = code.len().saturating_sub(1).to_string().len();
+    let mut out = String::new();
+    for (pc, op) in code.iter().enumerate() {
+        if let Some(label) = labels.get(&pc) {
+            writeln!(out, "{}:", label).unwrap();
+        }
+        let text = match op {
+            Op::Jmp(offset) => match target(code, pc, *offset).and_then(|t| labels.get(&t)) {
+                Some(label) => format!("jmp {}", label),
+                None => op.to_string(),
+            },
+            _ => op.to_string(),
+        };
+        match op {
+            Op::Jmp(offset) => {
+                let to = match (pc as i64).checked_add(*offset) {
+                    Some(to) => to.to_string(),
+                    None => "out of range".to_string(),
+                };
+                writeln!(out, "{:>w$}: {:<10}; -> {}", pc, text, to, w = width)
+            }
+            _ => writeln!(out, "{:>w$}: {}", pc, text, w = width),
+        }
+        .unwrap();
+    }
+    if let Some(label) = labels.get(&code.len()) {
+        writeln!(out, "{}:", label).unwrap();
+    }
+    out
+}
+
+/// Lists `code` with addresses and the targets of jumps.
+pub fn disassemble(code: &[Op]) -> String {
+    listing(code, &HashMap::new())
+}
+
+/// Like `disassemble`, but every jump that stays in the program goes to a
+/// label named after its target, e.g. `jmp L6`.
+pub fn disassemble_with_labels(code: &[Op]) -> String {
+    let labels = code
+        .iter()
+        .enumerate()
+        .filter_map(|(pc, op)| match op {
+            Op::Jmp(offset) => target(code, pc, *offset),
+            _ => None,
+        })
+        .map(|t| (t, format!("L{}", t)))
+        .collect();
+    listing(code, &labels)
+}
+
+/// Reads a listing back into a program. Labels resolve to the offset from
+/// the instruction using them, so the result is the same as for the plain
+/// puzzle format.
+pub fn assemble(text: &str) -> Result<Vec<Op>> {
+    let mut code = Vec::new();
+    let mut labels = HashMap::new();
+    // Instructions using a label: pc, label and the line, for errors
+    let mut fixups = Vec::new();
+
+    for (idx, line) in text.lines().enumerate() {
+        let error = |message: String| Error::Assemble {
+            line: idx + 1,
+            message,
+        };
+        let mut line = line.split(';').next().unwrap_or_default().trim();
+
+        // Any number of `<address>:` and `<label>:` prefixes
+        while let Some((prefix, rest)) = line.split_once(':') {
+            let prefix = prefix.trim();
+            if is_label(prefix) {
+                if labels.insert(prefix.to_string(), code.len()).is_some() {
+                    return Err(error(format!("label {} defined twice", prefix)));
+                }
+            } else if prefix.parse::<usize>().is_err() {
+                return Err(error(format!("invalid label {:?}", prefix)));
+            }
+            line = rest.trim();
+        }
+        if line.is_empty() {
+            continue;
+        }
+
+        let (mnemonic, operand) = line
+            .split_once(char::is_whitespace)
+            .map(|(m, o)| (m, o.trim()))
+            .unwrap_or((line, ""));
+        if operand.is_empty() && mnemonic != "end" {
+            return Err(error(format!("missing operand in {:?}", line)));
+        }
+        let invalid = || error(format!("invalid operand {:?}", operand));
+        code.push(match mnemonic {
+            "nop" | "acc" | "jmp" | "data" => {
+                let value = match operand.parse::<i64>() {
+                    Ok(value) => value,
+                    Err(_) if is_label(operand) && matches!(mnemonic, "jmp" | "nop") => {
+                        fixups.push((code.len(), operand, idx + 1));
+                        0
+                    }
+                    Err(_) => return Err(invalid()),
+                };
+                match mnemonic {
+                    "nop" => Op::Nop(value),
+                    "acc" => Op::Acc(value),
+                    "jmp" => Op::Jmp(value),
+                    _ => Op::Data(value),
+                }
+            }
+            "add" | "mul" | "store" | "load" | "jt" | "jf" | "lt" | "eq" | "arb" | "end" => {
+                let params = operand
+                    .split(',')
+                    .filter(|_| !operand.is_empty())
+                    .map(|p| param(p.trim()))
+                    .collect::<Option<Vec<_>>>();
+                params
+                    .and_then(|params| intcode(mnemonic, &params))
+                    .ok_or_else(invalid)?
+            }
+            _ => return Err(error(format!("unknown instruction {:?}", mnemonic))),
+        });
+    }
+
+    for (pc, label, line) in fixups {
+        let target = labels.get(label).ok_or_else(|| Error::Assemble {
+            line,
+            message: format!("undefined label {}", label),
+        })?;
+        let offset = *target as i64 - pc as i64;
+        match &mut code[pc] {
+            Op::Nop(v) | Op::Jmp(v) => *v = offset,
+            _ => unreachable!(),
+        }
+    }
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intcode::{Op, EXAMPLE};
+
+    #[test]
+    fn asm_round_trip() {
+        // Jumping back to the start, and out of the program
+        let program = format!("{}\njmp -9\njmp +3", EXAMPLE);
+        let code = super::assemble(&program).unwrap();
+        assert_eq!(code.len(), 11);
+
+        let listing = super::disassemble(&code);
+        assert_eq!(listing.lines().nth(2), Some(" 2: jmp +4    ; -> 6"));
+        assert_eq!(listing.lines().nth(9), Some(" 9: jmp -9    ; -> 0"));
+        assert_eq!(super::assemble(&listing).unwrap(), code);
+
+        let listing = super::disassemble_with_labels(&code);
+        assert!(listing.contains("L6:\n 6: acc +1\n"));
+        assert!(listing.contains(" 2: jmp L6    ; -> 6\n"));
+        // Out of the program, so no label
+        assert!(listing.contains("10: jmp +3    ; -> 13\n"));
+        assert_eq!(super::assemble(&listing).unwrap(), code);
+
+        let code = vec![Op::Nop(0), Op::Jmp(i64::MAX)];
+        let listing = super::disassemble(&code);
+        assert_eq!(
+            listing.lines().nth(1),
+            Some("1: jmp +9223372036854775807; -> out of range")
+        );
+        assert_eq!(super::assemble(&listing).unwrap(), code);
+    }
+
+    #[test]
+    fn asm_intcode() {
+        use crate::intcode::Param::{Immediate, Position, Relative};
+        let code = vec![
+            Op::Acc(1),
+            Op::Add(Immediate(1), Immediate(2), Position(0)),
+            Op::Mul(Relative(-3), Position(4), Relative(5)),
+            Op::Store(Relative(0)),
+            Op::Load(Immediate(-7)),
+            Op::JumpIfTrue(Position(1), Immediate(9)),
+            Op::JumpIfFalse(Immediate(0), Relative(2)),
+            Op::LT(Immediate(1), Immediate(2), Position(3)),
+            Op::Eq(Position(1), Position(2), Position(3)),
+            Op::AdjRelBase(Immediate(-4)),
+            Op::End,
+            Op::Data(-12345),
+        ];
+        let listing = super::disassemble(&code);
+        assert_eq!(listing.lines().nth(1), Some(" 1: add 1, 2, [0]"));
+        assert_eq!(super::assemble(&listing).unwrap(), code);
+    }
+
+    #[test]
+    fn asm_labels() {
+        let code = super::assemble(
+            "start: acc +1 ; count
+            loop:
+                jmp done
+                nop start
+                jmp loop
+            done:",
+        )
+        .unwrap();
+        assert_eq!(code, vec![Op::Acc(1), Op::Jmp(3), Op::Nop(-2), Op::Jmp(-2)]);
+    }
+
+    #[test]
+    fn asm_errors() {
+        let error = |text| super::assemble(text).unwrap_err().to_string();
+        assert_eq!(
+            error("acc +1\njmp nowhere"),
+            "line 2: undefined label nowhere"
+        );
+        assert_eq!(
+            error("a: nop +0\na: nop +0"),
+            "line 2: label a defined twice"
+        );
+        assert_eq!(error("a: acc a"), "line 1: invalid operand \"a\"");
+        assert_eq!(error("div +2"), "line 1: unknown instruction \"div\"");
+        assert_eq!(error("mul +2"), "line 1: invalid operand \"+2\"");
+        assert_eq!(error("end 1"), "line 1: invalid operand \"1\"");
+        assert_eq!(error("load [rb]"), "line 1: invalid operand \"[rb]\"");
+        assert_eq!(error("nop"), "line 1: missing operand in \"nop\"");
+    }
+}
@@ -0,0 +1,508 @@
+//! A cellular automaton engine. Cells are alive or dead, and every
+//! generation they are born or survive depending on how many of their
+//! neighbours are alive. What the cells and their neighbours are is up to
+//! the `Space`: an N-dimensional lattice, possibly folded along its
+//! symmetries, a hex grid or any bounded set of cells, such as the seats on
+//! a grid.
+
+use crate::hex::Cube;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::ops::RangeInclusive;
+
+/// Where the cells of an automaton are.
+pub trait Space {
+    type Cell: Clone + Eq + Hash;
+
+    /// Calls `f` with every neighbour of `cell`.
+    fn for_each_neighbour<F: FnMut(Self::Cell)>(&self, cell: &Self::Cell, f: F);
+
+    /// Calls `f` with every neighbour of `cell` and how many live neighbours
+    /// `cell` being alive gives it. That is 1 unless cells stand for several,
+    /// see `multiplicity`.
+    fn for_each_weighted_neighbour<F: FnMut(Self::Cell, usize)>(
+        &self,
+        cell: &Self::Cell,
+        mut f: F,
+    ) {
+        self.for_each_neighbour(cell, |nbr| f(nbr, 1));
+    }
+
+    /// How many cells `cell` stands for, in a space folded by its symmetries.
+    fn multiplicity(&self, _cell: &Self::Cell) -> usize {
+        1
+    }
+
+    /// Every cell, if there are finitely many. Only needed for rules that
+    /// bring cells without live neighbours to life.
+    fn cells(&self) -> Option<Vec<Self::Cell>> {
+        None
+    }
+
+    /// The live cells of the generation after `live`. Spaces that can count
+    /// live neighbours faster than in a map override this.
+    fn next(&self, rule: &Rule, live: &HashSet<Self::Cell>) -> HashSet<Self::Cell>
+    where
+        Self: Sized,
+    {
+        count_next(self, rule, live)
+    }
+}
+
+/// The generation after `live`, with live neighbours counted in a map.
+fn count_next<S: Space>(space: &S, rule: &Rule, live: &HashSet<S::Cell>) -> HashSet<S::Cell> {
+    let mut counts = HashMap::new();
+    for cell in live {
+        space.for_each_weighted_neighbour(cell, |nbr, weight| {
+            *counts.entry(nbr).or_insert(0) += weight
+        });
+    }
+    let mut candidates = counts.keys().cloned().collect::<HashSet<_>>();
+    candidates.extend(live.iter().cloned());
+    if rule.birth.contains(&0) {
+        let cells = space.cells();
+        candidates.extend(cells.expect("infinitely many cells would be born"));
+    }
+
+    candidates
+        .into_iter()
+        .filter(|cell| {
+            let count = counts.get(cell).copied().unwrap_or(0);
+            rule.next(live.contains(cell), count)
+        })
+        .collect()
+}
+
+/// The N-dimensional integer lattice, where every cell differing by at
+/// most 1 in each coordinate is a neighbour.
+pub struct Lattice<const N: usize>;
+
+impl<const N: usize> Space for Lattice<N> {
+    type Cell = [i64; N];
+
+    fn for_each_neighbour<F: FnMut(Self::Cell)>(&self, cell: &Self::Cell, mut f: F) {
+        let mut delta = [-1i64; N];
+        loop {
+            if delta.iter().any(|d| *d != 0) {
+                let mut nbr = *cell;
+                nbr.iter_mut().zip(&delta).for_each(|(c, d)| *c += d);
+                f(nbr);
+            }
+            // Count through all deltas in base 3
+            match delta.iter().position(|d| *d < 1) {
+                Some(i) => {
+                    delta[i] += 1;
+                    delta[..i].iter_mut().for_each(|d| *d = -1);
+                }
+                None => return,
+            }
+        }
+    }
+}
+
+/// The N-dimensional lattice folded along the mirror symmetries of all but
+/// its first `free` dimensions. That only works for automata whose cells
+/// stay the same when any of those coordinates is negated or two of them
+/// swapped, as when they all start at 0. A cell then stands for all cells
+/// it can be turned into that way, and is kept with those coordinates
+/// non-negative and in ascending order, which makes runs in many dimensions
+/// far faster. Live cells must be given in that form.
+pub struct FoldedLattice<const N: usize> {
+    pub free: usize,
+}
+
+impl<const N: usize> FoldedLattice<N> {
+    /// `cell` with its folded coordinates made non-negative and sorted.
+    pub fn fold(&self, mut cell: [i64; N]) -> [i64; N] {
+        let folded = &mut cell[self.free..];
+        folded.iter_mut().for_each(|c| *c = c.abs());
+        folded.sort_unstable();
+        cell
+    }
+}
+
+impl<const N: usize> Space for FoldedLattice<N> {
+    type Cell = [i64; N];
+
+    fn for_each_neighbour<F: FnMut(Self::Cell)>(&self, cell: &Self::Cell, mut f: F) {
+        self.for_each_weighted_neighbour(cell, |nbr, _| f(nbr));
+    }
+
+    fn for_each_weighted_neighbour<F: FnMut(Self::Cell, usize)>(
+        &self,
+        cell: &Self::Cell,
+        mut f: F,
+    ) {
+        let mut hits = HashMap::new();
+        Lattice::<N>.for_each_neighbour(cell, |nbr| *hits.entry(self.fold(nbr)).or_insert(0) += 1);
+        // Of the cells `nbr` stands for, `hits` are next to each one `cell`
+        // stands for. Counting those pairs from the other side gives how
+        // many of the cells `cell` stands for are next to each of them.
+        let multiplicity = self.multiplicity(cell);
+        for (nbr, hits) in hits {
+            let weight = hits * multiplicity / self.multiplicity(&nbr);
+            f(nbr, weight);
+        }
+    }
+
+    fn multiplicity(&self, cell: &Self::Cell) -> usize {
+        let folded = &cell[self.free..];
+        // Every non-zero coordinate can be negated, and all can be ordered
+        // in any of n! ways, of which those only swapping equal ones are
+        // the same
+        let signs = 1 << folded.iter().filter(|c| **c != 0).count();
+        let factorial = |n: usize| (1..=n).product::<usize>();
+        let mut orders = factorial(folded.len());
+        let mut rest = folded;
+        while let Some(first) = rest.first() {
+            let run = rest.iter().take_while(|c| *c == first).count();
+            orders /= factorial(run);
+            rest = &rest[run..];
+        }
+        signs * orders
+    }
+}
+
+/// An infinite grid of hexagons.
+pub struct Hex;
+
+impl Space for Hex {
+    type Cell = Cube;
+
+    fn for_each_neighbour<F: FnMut(Self::Cell)>(&self, cell: &Self::Cell, f: F) {
+        cell.neighbours().for_each(f);
+    }
+}
+
+/// A finite set of cells, with the neighbours of each worked out once and
+/// kept by index, so that a generation is counted in a plain array.
+pub struct Bounded<C> {
+    cells: Vec<C>,
+    index: HashMap<C, usize>,
+    neighbours: Vec<Vec<usize>>,
+}
+
+impl<C: Clone + Eq + Hash> Bounded<C> {
+    /// The space of `cells`, each with the neighbours `neighbours` returns.
+    /// Neighbours that aren't among `cells` are left out.
+    pub fn new<F, I>(cells: impl IntoIterator<Item = C>, neighbours: F) -> Self
+    where
+        F: Fn(&C) -> I,
+        I: IntoIterator<Item = C>,
+    {
+        let cells = cells.into_iter().collect::<Vec<_>>();
+        let index = cells
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (c.clone(), i))
+            .collect::<HashMap<_, _>>();
+        let neighbours = cells
+            .iter()
+            .map(|c| {
+                let nbrs = neighbours(c).into_iter();
+                nbrs.filter_map(|nbr| index.get(&nbr).copied()).collect()
+            })
+            .collect();
+        Bounded {
+            cells,
+            index,
+            neighbours,
+        }
+    }
+}
+
+impl<C: Clone + Eq + Hash> Space for Bounded<C> {
+    type Cell = C;
+
+    fn for_each_neighbour<F: FnMut(C)>(&self, cell: &C, f: F) {
+        let nbrs = self.index.get(cell).map(|i| &self.neighbours[*i]);
+        nbrs.into_iter()
+            .flatten()
+            .map(|j| self.cells[*j].clone())
+            .for_each(f);
+    }
+
+    fn cells(&self) -> Option<Vec<C>> {
+        Some(self.cells.clone())
+    }
+
+    fn next(&self, rule: &Rule, live: &HashSet<C>) -> HashSet<C> {
+        let mut alive = vec![false; self.cells.len()];
+        live.iter()
+            .filter_map(|cell| self.index.get(cell))
+            .for_each(|i| alive[*i] = true);
+        let mut counts = vec![0; self.cells.len()];
+        for (i, nbrs) in self.neighbours.iter().enumerate() {
+            if alive[i] {
+                nbrs.iter().for_each(|j| counts[*j] += 1);
+            }
+        }
+        let next = (0..self.cells.len()).filter(|i| rule.next(alive[*i], counts[*i]));
+        next.map(|i| self.cells[i].clone()).collect()
+    }
+}
+
+/// How many live neighbours bring a dead cell to life, and keep a live one
+/// alive.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rule {
+    pub birth: RangeInclusive<usize>,
+    pub survive: RangeInclusive<usize>,
+}
+
+impl Rule {
+    /// Conway's Game of Life.
+    pub fn life() -> Self {
+        Rule {
+            birth: 3..=3,
+            survive: 2..=3,
+        }
+    }
+
+    /// Whether a cell with `count` live neighbours is alive next generation.
+    pub fn next(&self, alive: bool, count: usize) -> bool {
+        if alive {
+            self.survive.contains(&count)
+        } else {
+            self.birth.contains(&count)
+        }
+    }
+}
+
+/// How `Automaton::run` ended.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    /// Ran all generations asked for.
+    Finished,
+    /// Nothing changed from `generation` on.
+    FixedPoint { generation: usize },
+    /// The generation `start + period` was the same as `start`, the run
+    /// then skipped ahead over whole periods.
+    Cycle { start: usize, period: usize },
+}
+
+pub struct Automaton<S: Space> {
+    space: S,
+    rule: Rule,
+    live: HashSet<S::Cell>,
+    generation: usize,
+}
+
+impl<S: Space> Automaton<S> {
+    pub fn new(space: S, rule: Rule, live: impl IntoIterator<Item = S::Cell>) -> Self {
+        Automaton {
+            space,
+            rule,
+            live: live.into_iter().collect(),
+            generation: 0,
+        }
+    }
+
+    pub fn live(&self) -> &HashSet<S::Cell> {
+        &self.live
+    }
+
+    /// The number of live cells, counting all those a cell stands for.
+    pub fn population(&self) -> usize {
+        self.live.iter().map(|c| self.space.multiplicity(c)).sum()
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Advances one generation, returning whether anything changed.
+    ///
+    /// # Panics
+    ///
+    /// If the rule brings cells without live neighbours to life in a space
+    /// with infinitely many cells.
+    pub fn step(&mut self) -> bool {
+        self.advance() != self.live
+    }
+
+    /// Advances one generation, returning the live cells of the last one.
+    fn advance(&mut self) -> HashSet<S::Cell> {
+        let next = self.space.next(&self.rule, &self.live);
+        self.generation += 1;
+        std::mem::replace(&mut self.live, next)
+    }
+
+    /// A hash of the live cells that doesn't depend on their order.
+    fn fingerprint(&self) -> u64 {
+        self.live
+            .iter()
+            .map(|cell| {
+                let mut hasher = DefaultHasher::new();
+                cell.hash(&mut hasher);
+                hasher.finish()
+            })
+            .fold(self.live.len() as u64, u64::wrapping_add)
+    }
+
+    /// Runs up to `generations` generations, or forever with `None`, and
+    /// stops early at a fixed point. A cycle is skipped over to where the
+    /// run would have ended, or stops it if it wouldn't. Every generation
+    /// is kept to spot cycles, found by a hash of their live cells.
+    pub fn run(&mut self, generations: Option<usize>) -> Outcome {
+        let first = self.generation;
+        let mut history = Vec::new();
+        let mut seen = HashMap::<_, Vec<usize>>::new();
+        let end = generations.map(|g| self.generation + g);
+        while end.is_none_or(|end| self.generation < end) {
+            crate::runner::checkpoint();
+            // Only generations with the same hash can match, but those that
+            // do may still differ
+            let same_hash = seen.entry(self.fingerprint()).or_default();
+            let earlier = same_hash
+                .iter()
+                .copied()
+                .find(|g| history[g - first] == self.live);
+            same_hash.push(self.generation);
+            if let Some(start) = earlier {
+                let period = self.generation - start;
+                if let Some(end) = end {
+                    for _ in 0..(end - self.generation) % period {
+                        self.step();
+                    }
+                    self.generation = end;
+                }
+                return Outcome::Cycle { start, period };
+            }
+            let last = self.advance();
+            let changed = last != self.live;
+            history.push(last);
+            if !changed {
+                return Outcome::FixedPoint {
+                    generation: self.generation - 1,
+                };
+            }
+        }
+        Outcome::Finished
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Automaton, Bounded, FoldedLattice, Hex, Lattice, Outcome, Rule, Space};
+    use crate::hex::Cube;
+
+    #[test]
+    fn automaton_lattice() {
+        let mut count = 0;
+        Lattice::<3>.for_each_neighbour(&[0, 0, 0], |_| count += 1);
+        assert_eq!(count, 26);
+
+        // A blinker
+        let live = vec![[0, -1], [0, 0], [0, 1]];
+        let mut life = Automaton::new(Lattice::<2>, Rule::life(), live.clone());
+        assert!(life.step());
+        assert!(life.live().contains(&[-1, 0]) && life.live().contains(&[1, 0]));
+        assert_eq!(
+            life.run(Some(11)),
+            Outcome::Cycle {
+                start: 1,
+                period: 2
+            }
+        );
+        assert_eq!(life.generation(), 12);
+        assert_eq!(life.live(), &live.into_iter().collect());
+
+        // A block stays as it is
+        let live = vec![[0, 0], [0, 1], [1, 0], [1, 1]];
+        let mut life = Automaton::new(Lattice::<2>, Rule::life(), live);
+        assert_eq!(life.run(None), Outcome::FixedPoint { generation: 0 });
+    }
+
+    #[test]
+    fn automaton_folded_lattice() {
+        let folded = FoldedLattice::<5> { free: 2 };
+        assert_eq!(folded.fold([1, -2, 1, -1, 0]), [1, -2, 0, 1, 1]);
+        assert_eq!(folded.multiplicity(&[1, -2, 0, 1, 1]), 12);
+        let mut cells = 0;
+        folded.for_each_weighted_neighbour(&[0; 5], |nbr, _| cells += folded.multiplicity(&nbr));
+        assert_eq!(cells, 242);
+
+        // A glider lying flat grows the same either way
+        let glider = vec![
+            [1, 0, 0, 0, 0],
+            [2, 1, 0, 0, 0],
+            [0, 2, 0, 0, 0],
+            [1, 2, 0, 0, 0],
+            [2, 2, 0, 0, 0],
+        ];
+        let mut full = Automaton::new(Lattice::<5>, Rule::life(), glider.clone());
+        let mut folded = Automaton::new(folded, Rule::life(), glider);
+        for _ in 0..3 {
+            full.step();
+            folded.step();
+            assert_eq!(folded.population(), full.population());
+            assert!(folded.live().len() < full.live().len());
+        }
+    }
+
+    #[test]
+    fn automaton_hex() {
+        // Three in a row each have 1 or 2 live neighbours and survive, the
+        // two cells next to both gaps between them are born
+        let rule = Rule {
+            birth: 2..=2,
+            survive: 1..=2,
+        };
+        let row = (0..3).map(|q| Cube::new(q, 0));
+        let mut hex = Automaton::new(Hex, rule, row);
+        hex.step();
+        assert_eq!(hex.live().len(), 7);
+    }
+
+    #[test]
+    fn automaton_bounded() {
+        // A ring of 5 cells where a cell is born alone and dies crowded
+        let ring = Bounded::new(0..5, |c: &i32| vec![(c + 1) % 5, (c + 4) % 5]);
+        let rule = Rule {
+            birth: 0..=0,
+            survive: 0..=1,
+        };
+        let mut ring = Automaton::new(ring, rule, vec![]);
+        ring.step();
+        assert_eq!(ring.live().len(), 5);
+        ring.step();
+        assert_eq!(ring.live().len(), 0);
+        assert_eq!(
+            ring.run(None),
+            Outcome::Cycle {
+                start: 2,
+                period: 2
+            }
+        );
+    }
+
+    /// A cell whose hash says nothing about it.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    struct Unhashed(i32);
+
+    impl std::hash::Hash for Unhashed {
+        fn hash<H: std::hash::Hasher>(&self, _: &mut H) {}
+    }
+
+    #[test]
+    fn automaton_hash_collision() {
+        // A single cell going round a ring of 4, every generation hashing
+        // the same
+        let ring = Bounded::new((0..4).map(Unhashed), |c| Some(Unhashed((c.0 + 1) % 4)));
+        let rule = Rule {
+            birth: 1..=1,
+            survive: 2..=2,
+        };
+        let mut ring = Automaton::new(ring, rule, Some(Unhashed(0)));
+        assert_eq!(
+            ring.run(Some(10)),
+            Outcome::Cycle {
+                start: 0,
+                period: 4
+            }
+        );
+        assert_eq!(ring.live(), &Some(Unhashed(2)).into_iter().collect());
+    }
+}
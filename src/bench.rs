@@ -0,0 +1,279 @@
+//! A small benchmark harness in the spirit of criterion. Each routine is
+//! warmed up, then timed over a number of samples that batch enough
+//! iterations to be measurable. Sample medians can be saved as a named
+//! baseline and compared against on the next run.
+
+use crate::error::{Error, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// A change in median smaller than this is reported as noise.
+pub const NOISE_THRESHOLD: f64 = 0.05;
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    pub warm_up: Duration,
+    pub measurement: Duration,
+    pub sample_size: usize,
+    /// Routines too slow to fill `sample_size` samples in `measurement`
+    /// still get this many.
+    pub min_samples: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            warm_up: Duration::from_millis(300),
+            measurement: Duration::from_secs(1),
+            sample_size: 50,
+            min_samples: 3,
+        }
+    }
+}
+
+/// Per-iteration times of all samples, in nanoseconds.
+#[derive(Clone, Debug)]
+pub struct Stats {
+    samples: Vec<f64>,
+}
+
+impl Stats {
+    pub fn new(mut samples: Vec<f64>) -> Self {
+        assert!(!samples.is_empty());
+        samples.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        Stats { samples }
+    }
+
+    pub fn samples(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn min(&self) -> f64 {
+        self.samples[0]
+    }
+
+    pub fn max(&self) -> f64 {
+        self.samples[self.samples.len() - 1]
+    }
+
+    pub fn median(&self) -> f64 {
+        let n = self.samples.len();
+        if n % 2 == 1 {
+            self.samples[n / 2]
+        } else {
+            (self.samples[n / 2 - 1] + self.samples[n / 2]) / 2.0
+        }
+    }
+
+    pub fn mean(&self) -> f64 {
+        self.samples.iter().sum::<f64>() / self.samples.len() as f64
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        let mean = self.mean();
+        let var = self.samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>()
+            / self.samples.len() as f64;
+        var.sqrt()
+    }
+}
+
+/// Times `routine` according to `config`.
+pub fn measure<F: FnMut()>(config: &Config, mut routine: F) -> Stats {
+    // Warm up, and estimate the time of one iteration while at it
+    let start = Instant::now();
+    let mut iters = 0u32;
+    while iters == 0 || start.elapsed() < config.warm_up {
+        routine();
+        iters += 1;
+    }
+    let per_iter = start.elapsed() / iters;
+
+    let per_sample = config.measurement / config.sample_size as u32;
+    let batch = (per_sample.as_nanos() / per_iter.as_nanos().max(1)).max(1) as u32;
+    let samples = (config.measurement.as_nanos() / (per_iter * batch).as_nanos().max(1)) as usize;
+    let samples = samples.clamp(config.min_samples, config.sample_size);
+
+    let samples = (0..samples)
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..batch {
+                routine();
+            }
+            start.elapsed().as_nanos() as f64 / batch as f64
+        })
+        .collect();
+    Stats::new(samples)
+}
+
+/// How a median compares to the one in a baseline.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Change {
+    Improved(f64),
+    Regressed(f64),
+    NoChange(f64),
+}
+
+impl Change {
+    pub fn new(old: f64, new: f64) -> Self {
+        let change = (new - old) / old;
+        if change > NOISE_THRESHOLD {
+            Change::Regressed(change)
+        } else if change < -NOISE_THRESHOLD {
+            Change::Improved(change)
+        } else {
+            Change::NoChange(change)
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Change::Improved(c) => write!(f, "{:+.1}% (improved)", c * 100.0),
+            Change::Regressed(c) => write!(f, "{:+.1}% (regressed)", c * 100.0),
+            Change::NoChange(c) => write!(f, "{:+.1}% (no change)", c * 100.0),
+        }
+    }
+}
+
+/// Median times by benchmark id, stored one `<id> <nanoseconds>` per line.
+#[derive(Debug, Default)]
+pub struct Baseline {
+    medians: BTreeMap<String, f64>,
+}
+
+impl Baseline {
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut medians = BTreeMap::new();
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (id, median) = line
+                .split_once(' ')
+                .and_then(|(id, median)| Some((id, median.trim().parse().ok()?)))
+                .ok_or_else(|| Error::Baseline {
+                    line: idx + 1,
+                    text: line.to_string(),
+                })?;
+            medians.insert(id.to_string(), median);
+        }
+        Ok(Baseline { medians })
+    }
+
+    /// Loads a saved baseline, or an empty one if there is none yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(source) => Err(Error::Io {
+                path: path.to_path_buf(),
+                source,
+            }),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let io_error = |source| Error::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(io_error)?;
+        }
+        fs::write(path, self.to_string()).map_err(io_error)
+    }
+
+    pub fn get(&self, id: &str) -> Option<f64> {
+        self.medians.get(id).copied()
+    }
+
+    pub fn insert(&mut self, id: &str, median: f64) {
+        self.medians.insert(id.to_string(), median);
+    }
+
+    /// Adds all medians of `other`, replacing those with the same id.
+    pub fn extend(&mut self, other: &Baseline) {
+        self.medians
+            .extend(other.medians.iter().map(|(id, m)| (id.clone(), *m)));
+    }
+}
+
+impl fmt::Display for Baseline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (id, median) in &self.medians {
+            writeln!(f, "{} {}", id, median)?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats nanoseconds with a unit that keeps a few significant digits.
+pub fn format_ns(ns: f64) -> String {
+    if ns < 1e3 {
+        format!("{:.1}ns", ns)
+    } else if ns < 1e6 {
+        format!("{:.2}µs", ns / 1e3)
+    } else if ns < 1e9 {
+        format!("{:.2}ms", ns / 1e6)
+    } else {
+        format!("{:.3}s", ns / 1e9)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    #[test]
+    fn bench_stats() {
+        let stats = super::Stats::new(vec![4.0, 1.0, 3.0, 2.0]);
+        assert_eq!(stats.min(), 1.0);
+        assert_eq!(stats.max(), 4.0);
+        assert_eq!(stats.median(), 2.5);
+        assert_eq!(stats.mean(), 2.5);
+        assert!((stats.std_dev() - 1.25f64.sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn bench_measure() {
+        let config = super::Config {
+            warm_up: Duration::from_millis(1),
+            measurement: Duration::from_millis(10),
+            sample_size: 10,
+            min_samples: 2,
+        };
+        let mut calls = 0;
+        let stats = super::measure(&config, || calls += 1);
+        assert!((2..=10).contains(&stats.samples()));
+        assert!(calls >= stats.samples());
+    }
+
+    #[test]
+    fn bench_baseline() {
+        let mut baseline = super::Baseline::parse("# medians\nday01/input/parse 1500.5\n").unwrap();
+        assert_eq!(baseline.get("day01/input/parse"), Some(1500.5));
+        baseline.insert("day01/input/part1", 20.0);
+        let text = baseline.to_string();
+        assert_eq!(text, "day01/input/parse 1500.5\nday01/input/part1 20\n");
+        assert!(super::Baseline::parse(&text).is_ok());
+
+        assert!(matches!(
+            super::Baseline::parse("day01/input/parse fast"),
+            Err(crate::error::Error::Baseline { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn bench_change() {
+        use super::Change;
+        assert_eq!(Change::new(100.0, 150.0), Change::Regressed(0.5));
+        assert_eq!(Change::new(100.0, 50.0), Change::Improved(-0.5));
+        assert_eq!(Change::new(100.0, 102.0).to_string(), "+2.0% (no change)");
+        assert_eq!(super::format_ns(1_234.0), "1.23µs");
+    }
+}
@@ -0,0 +1,319 @@
+//! A line-driven debugger for `intcode::CPU`, behind the `debug` command of
+//! the binary. Each command returns the text to show for it, and `HELP`
+//! lists them. Commands can be shortened to their first letter.
+
+use crate::intcode::{Step, CPU};
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt::Write;
+
+pub const HELP: &str = "\
+step [n]          execute n instructions (default 1)
+continue          run until a breakpoint, a watch, the end, or a
+                  handheld instruction that already ran since continuing
+break <pc|op>     stop before the instruction at pc, or any op
+delete <pc|op>    remove a breakpoint
+watch acc         stop whenever acc changes
+unwatch acc       stop watching acc
+list [n]          show n instructions either side of pc (default 3)
+rewind [n]        undo the last n instructions (default 1)
+info              show pc, acc, breakpoints and watches
+help
+quit
+";
+
+/// How many earlier states `rewind` can go back to.
+pub const HISTORY_LIMIT: usize = 1_000;
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Breakpoint {
+    Pc(usize),
+    /// Any instruction with this mnemonic.
+    Op(String),
+}
+
+impl Breakpoint {
+    fn parse(arg: &str) -> Option<Self> {
+        match arg.parse() {
+            Ok(pc) => Some(Breakpoint::Pc(pc)),
+            Err(_) if !arg.is_empty() && arg.chars().all(|c| c.is_ascii_alphabetic()) => {
+                Some(Breakpoint::Op(arg.to_string()))
+            }
+            Err(_) => None,
+        }
+    }
+}
+
+/// Why `step` or `continue` stopped before running out of instructions.
+enum Stop {
+    Breakpoint,
+    Watch(i64),
+    Halted,
+    NeedsInput,
+    Fault(String),
+    /// A handheld program is about to repeat an instruction, and so loops.
+    Loop,
+}
+
+pub struct Debugger {
+    cpu: CPU,
+    history: VecDeque<CPU>,
+    breakpoints: BTreeSet<Breakpoint>,
+    watch_acc: bool,
+}
+
+impl Debugger {
+    pub fn new(cpu: CPU) -> Self {
+        Debugger {
+            cpu,
+            history: VecDeque::new(),
+            breakpoints: BTreeSet::new(),
+            watch_acc: false,
+        }
+    }
+
+    pub fn cpu(&self) -> &CPU {
+        &self.cpu
+    }
+
+    fn at_breakpoint(&self) -> bool {
+        let op = match self.cpu.fetch() {
+            Some((op, _)) => op,
+            None => return false,
+        };
+        self.breakpoints.iter().any(|b| match b {
+            Breakpoint::Pc(pc) => *pc == self.cpu.pc,
+            Breakpoint::Op(name) => name == op.mnemonic(),
+        })
+    }
+
+    /// Executes one instruction, keeping the state before it for `rewind`.
+    fn step_one(&mut self) -> Option<Stop> {
+        let before = self.cpu.clone();
+        match self.cpu.step() {
+            Ok(Step::Executed) => {}
+            Ok(Step::Halted) => return Some(Stop::Halted),
+            Ok(Step::NeedsInput) => return Some(Stop::NeedsInput),
+            Err(fault) => return Some(Stop::Fault(fault.to_string())),
+        }
+        let old_acc = before.acc;
+        if self.history.len() == HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+        self.history.push_back(before);
+        if self.watch_acc && self.cpu.acc != old_acc {
+            return Some(Stop::Watch(old_acc));
+        }
+        None
+    }
+
+    /// Executes up to `count` instructions, or all of them with `None`.
+    /// Breakpoints only apply from the second instruction on, so that
+    /// continuing from one makes progress. A handheld program that gets
+    /// back to an instruction it ran in this call loops forever, so that
+    /// stops it too.
+    fn run(&mut self, count: Option<usize>) -> (usize, Option<Stop>) {
+        let mut visited = BTreeSet::new();
+        let mut executed = 0;
+        while count.is_none_or(|c| executed < c) {
+            if executed > 0 && self.at_breakpoint() {
+                return (executed, Some(Stop::Breakpoint));
+            }
+            if self.cpu.is_handheld() && !visited.insert(self.cpu.pc) {
+                return (executed, Some(Stop::Loop));
+            }
+            let stop = self.step_one();
+            if !matches!(stop, Some(Stop::Halted | Stop::NeedsInput | Stop::Fault(_))) {
+                executed += 1;
+            }
+            if stop.is_some() {
+                return (executed, stop);
+            }
+        }
+        (executed, None)
+    }
+
+    /// The current instruction, e.g. `pc 2: jmp +4 (acc 1)`.
+    fn position(&self) -> String {
+        match self.cpu.fetch() {
+            Some((op, _)) => format!("pc {}: {} (acc {})", self.cpu.pc, op, self.cpu.acc),
+            None => format!("pc {}: end of program (acc {})", self.cpu.pc, self.cpu.acc),
+        }
+    }
+
+    fn report(&self, executed: usize, stop: Option<Stop>) -> String {
+        let reason = match stop {
+            None => return format!("{}\n", self.position()),
+            Some(Stop::Breakpoint) => "breakpoint".to_string(),
+            Some(Stop::Watch(old)) => format!("acc changed from {} to {}", old, self.cpu.acc),
+            Some(Stop::Halted) => "halted".to_string(),
+            Some(Stop::NeedsInput) => "waiting for input".to_string(),
+            Some(Stop::Fault(fault)) => format!("fault: {}", fault),
+            Some(Stop::Loop) => "instruction about to run a second time".to_string(),
+        };
+        format!(
+            "{} after {} instructions\n{}\n",
+            reason,
+            executed,
+            self.position()
+        )
+    }
+
+    /// The instructions from `before` before pc to `after` after it, with
+    /// pc marked. Intcode can't be decoded backwards, so for it the window
+    /// starts at pc.
+    fn window(&self, before: usize, after: usize) -> String {
+        let mut out = String::new();
+        let mark = |addr: usize| if addr == self.cpu.pc { "=>" } else { "  " };
+        if self.cpu.is_handheld() {
+            let start = self.cpu.pc.saturating_sub(before);
+            for (addr, op) in self
+                .cpu
+                .code
+                .iter()
+                .enumerate()
+                .skip(start)
+                .take(before + after + 1)
+            {
+                writeln!(out, "{} {:>4}: {}", mark(addr), addr, op).unwrap();
+            }
+        } else {
+            let mut addr = self.cpu.pc;
+            for _ in 0..=after {
+                let (op, len) = crate::intcode::decode(&self.cpu.memory, addr);
+                writeln!(out, "{} {:>4}: {}", mark(addr), addr, op).unwrap();
+                addr += len;
+            }
+        }
+        if out.is_empty() {
+            out = format!("{}\n", self.position());
+        }
+        out
+    }
+
+    /// Runs one command line and returns what to show for it, or `None` to
+    /// quit.
+    pub fn command(&mut self, line: &str) -> Option<String> {
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or_default();
+        let arg = words.next();
+        let count = |default: usize| match arg {
+            None => Ok(default),
+            Some(n) => n.parse().map_err(|_| format!("not a number: {}\n", n)),
+        };
+
+        let reply = match command {
+            "s" | "step" => count(1).map(|n| {
+                let (executed, stop) = self.run(Some(n));
+                self.report(executed, stop)
+            }),
+            "c" | "continue" => {
+                let (executed, stop) = self.run(None);
+                Ok(self.report(executed, stop))
+            }
+            "b" | "break" => match arg.and_then(Breakpoint::parse) {
+                Some(b) => {
+                    self.breakpoints.insert(b);
+                    Ok(String::new())
+                }
+                None => Err("break needs a pc or an instruction name\n".to_string()),
+            },
+            "d" | "delete" => match arg.and_then(Breakpoint::parse) {
+                Some(b) if self.breakpoints.remove(&b) => Ok(String::new()),
+                _ => Err("no such breakpoint\n".to_string()),
+            },
+            "w" | "watch" | "u" | "unwatch" if arg == Some("acc") => {
+                self.watch_acc = command.starts_with('w');
+                Ok(String::new())
+            }
+            "w" | "watch" | "u" | "unwatch" => Err("only acc can be watched\n".to_string()),
+            "l" | "list" => count(3).map(|n| self.window(n, n)),
+            "r" | "rewind" => count(1).map(|n| {
+                let n = n.min(self.history.len());
+                if let Some(cpu) = self.history.drain(self.history.len() - n..).next() {
+                    self.cpu = cpu;
+                }
+                format!("rewound {} instructions\n{}\n", n, self.position())
+            }),
+            "i" | "info" => {
+                let mut out = format!("{}\n", self.position());
+                for b in &self.breakpoints {
+                    match b {
+                        Breakpoint::Pc(pc) => writeln!(out, "break at pc {}", pc),
+                        Breakpoint::Op(op) => writeln!(out, "break on {}", op),
+                    }
+                    .unwrap();
+                }
+                if self.watch_acc {
+                    out.push_str("watching acc\n");
+                }
+                Ok(out)
+            }
+            "h" | "help" => Ok(HELP.to_string()),
+            "q" | "quit" => return None,
+            "" => Ok(String::new()),
+            _ => Err(format!("unknown command {}, try help\n", command)),
+        };
+        Some(reply.unwrap_or_else(|e| e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intcode::{CPU, EXAMPLE};
+
+    fn debugger() -> super::Debugger {
+        super::Debugger::new(CPU::new(EXAMPLE).unwrap())
+    }
+
+    #[test]
+    fn debugger_step_and_rewind() {
+        let mut dbg = debugger();
+        assert_eq!(dbg.command("step 2").unwrap(), "pc 2: jmp +4 (acc 1)\n");
+        assert_eq!(dbg.command("s").unwrap(), "pc 6: acc +1 (acc 1)\n");
+        assert_eq!(
+            dbg.command("rewind 2").unwrap(),
+            "rewound 2 instructions\npc 1: acc +1 (acc 0)\n"
+        );
+        assert_eq!(
+            dbg.command("list 1").unwrap(),
+            "      0: nop +0\n=>    1: acc +1\n      2: jmp +4\n"
+        );
+        assert!(dbg.command("quit").is_none());
+    }
+
+    #[test]
+    fn debugger_breakpoints_and_watches() {
+        let mut dbg = debugger();
+        dbg.command("break jmp");
+        assert_eq!(
+            dbg.command("c").unwrap(),
+            "breakpoint after 2 instructions\npc 2: jmp +4 (acc 1)\n"
+        );
+        dbg.command("delete jmp");
+        dbg.command("break 4");
+        dbg.command("watch acc");
+        assert_eq!(
+            dbg.command("continue").unwrap(),
+            "acc changed from 1 to 2 after 2 instructions\npc 7: jmp -4 (acc 2)\n"
+        );
+        dbg.command("unwatch acc");
+        assert_eq!(
+            dbg.command("c").unwrap(),
+            "breakpoint after 2 instructions\npc 4: jmp -3 (acc 5)\n"
+        );
+        assert_eq!(
+            dbg.command("info").unwrap(),
+            "pc 4: jmp -3 (acc 5)\nbreak at pc 4\n"
+        );
+        dbg.command("delete 4");
+        assert_eq!(
+            dbg.command("c").unwrap(),
+            "instruction about to run a second time after 6 instructions\npc 4: jmp -3 (acc 10)\n"
+        );
+        assert_eq!(dbg.cpu().acc, 10);
+        assert_eq!(
+            dbg.command("bogus").unwrap(),
+            "unknown command bogus, try help\n"
+        );
+    }
+}
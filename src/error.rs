@@ -0,0 +1,109 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The input file for a day does not exist.
+    MissingInput { day: usize, path: PathBuf },
+    /// Any other failure reading an input.
+    Io { path: PathBuf, source: io::Error },
+    /// A line in the answers file that couldn't be parsed.
+    Answers { line: usize, text: String },
+    /// A line in a saved benchmark baseline that couldn't be parsed.
+    Baseline { line: usize, text: String },
+    /// A line in a saved CPU snapshot that couldn't be parsed.
+    Snapshot { line: usize, text: String },
+    /// Malformed puzzle input. `text` is the offending line and `column` is
+    /// where parsing stopped in it, both counted from 1. `day` is `None`
+    /// when the parser isn't tied to a day, e.g. the console VM's.
+    Parse {
+        day: Option<usize>,
+        line: usize,
+        column: usize,
+        text: String,
+    },
+    /// The solver ran to completion without finding an answer.
+    NoSolution { day: usize, part: usize },
+    /// There is no solver for the part yet.
+    NotImplemented { day: usize, part: usize },
+    /// A console program listing that couldn't be assembled.
+    Assemble { line: usize, message: String },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::MissingInput { day, path } => {
+                write!(f, "day {}: input file {} not found", day, path.display())
+            }
+            Error::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            Error::Answers { line, text } => {
+                write!(f, "answers file, line {}: cannot parse {:?}", line, text)
+            }
+            Error::Baseline { line, text } => {
+                write!(
+                    f,
+                    "benchmark baseline, line {}: cannot parse {:?}",
+                    line, text
+                )
+            }
+            Error::Snapshot { line, text } => {
+                write!(f, "snapshot, line {}: cannot parse {:?}", line, text)
+            }
+            Error::Parse {
+                day,
+                line,
+                column,
+                text,
+            } => {
+                if let Some(day) = day {
+                    write!(f, "day {}, ", day)?;
+                }
+                write!(
+                    f,
+                    "line {}, column {}: cannot parse {:?}",
+                    line, column, text
+                )
+            }
+            Error::NoSolution { day, part } => {
+                write!(f, "day {}, part {}: no solution found", day, part)
+            }
+            Error::NotImplemented { day, part } => {
+                write!(f, "day {}, part {}: not implemented yet", day, part)
+            }
+            Error::Assemble { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl Error {
+    /// Attaches `day` to a parse error that doesn't know it yet.
+    pub fn for_day(self, day: usize) -> Self {
+        match self {
+            Error::Parse {
+                day: None,
+                line,
+                column,
+                text,
+            } => Error::Parse {
+                day: Some(day),
+                line,
+                column,
+                text,
+            },
+            e => e,
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -1,9 +1,12 @@
+use crate::error::{self, Error};
 use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::character::complete::digit1;
 use nom::combinator::{map_res, opt};
 use nom::sequence::pair;
 use nom::IResult;
+use std::fmt;
+use std::ops::{Index, IndexMut};
 
 fn signed_to_val<I>(s: (Option<&str>, &str)) -> Result<I, std::num::ParseIntError>
 where
@@ -15,6 +18,7 @@ where
     }
 }
 
+/// A signed integer with an optional `+` or `-` sign.
 pub fn ival<I>(i: &str) -> IResult<&str, I>
 where
     I: std::str::FromStr<Err = std::num::ParseIntError> + std::ops::Neg<Output = I>,
@@ -23,10 +27,303 @@ where
     map_res(pair(opt(a), digit1), signed_to_val)(i)
 }
 
+/// An unsigned integer.
 pub fn uval<U: std::str::FromStr>(i: &str) -> IResult<&str, U> {
     map_res(digit1, |s: &str| s.parse::<U>())(i)
 }
 
+/// Builds a parse error for `input`, where `rest` is the unparsed tail of it.
+fn parse_error(input: &str, rest: &str) -> Error {
+    // A list parser stops before the separator, point at the line after it
+    let rest = rest.trim_start_matches(['\n', '\r']);
+    let offset = input.len() - rest.len();
+    let line_start = input[..offset].rfind('\n').map_or(0, |p| p + 1);
+    Error::Parse {
+        day: None,
+        line: input[..offset].matches('\n').count() + 1,
+        column: input[line_start..offset].chars().count() + 1,
+        text: input[line_start..].lines().next().unwrap_or("").to_string(),
+    }
+}
+
+/// Runs `parser` over all of `input`. Anything but trailing whitespace left
+/// unparsed is an error.
+pub fn parse_all<'a, O, P>(input: &'a str, mut parser: P) -> error::Result<O>
+where
+    P: FnMut(&'a str) -> IResult<&'a str, O>,
+{
+    match parser(input) {
+        Ok((rest, o)) if rest.trim().is_empty() => Ok(o),
+        Ok((rest, _)) => Err(parse_error(input, rest)),
+        Err(nom::Err::Error(e)) | Err(nom::Err::Failure(e)) => Err(parse_error(input, e.input)),
+        Err(nom::Err::Incomplete(_)) => Err(parse_error(input, "")),
+    }
+}
+
+/// `parse_all` for a day's puzzle input.
+pub fn parse_day<'a, O, P>(day: usize, input: &'a str, parser: P) -> error::Result<O>
+where
+    P: FnMut(&'a str) -> IResult<&'a str, O>,
+{
+    parse_all(input, parser).map_err(|e| e.for_day(day))
+}
+
+/// A position in a `Grid`, `(x, y)` from the top left.
+pub type Pos = (usize, usize);
+
+/// Steps to the 4 orthogonal neighbours, clockwise from up.
+pub const NEIGHBOURS4: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// Steps to all 8 neighbours, clockwise from up.
+pub const NEIGHBOURS8: [(isize, isize); 8] = [
+    (0, -1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+];
+
+/// A rectangular grid of cells, stored row by row.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// A grid with `cell` of each position in it.
+    pub fn from_fn<F: FnMut(Pos) -> T>(width: usize, height: usize, mut cell: F) -> Self {
+        Grid {
+            width,
+            height,
+            cells: (0..width * height)
+                .map(|i| cell((i % width, i / width)))
+                .collect(),
+        }
+    }
+
+    /// Parses a grid with one row per line, mapping every char to a cell
+    /// with `cell`. A char it returns `None` for, or a row not as long as
+    /// the first, is an error.
+    pub fn parse<F>(input: &str, mut cell: F) -> error::Result<Self>
+    where
+        F: FnMut(char) -> Option<T>,
+    {
+        let error = |line: usize, column: usize, text: &str| Error::Parse {
+            day: None,
+            line: line + 1,
+            column: column + 1,
+            text: text.to_string(),
+        };
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::new();
+        for (y, line) in input.trim_end().lines().enumerate() {
+            let mut len = 0;
+            for (x, c) in line.chars().enumerate() {
+                if width.is_some_and(|w| x >= w) {
+                    return Err(error(y, x, line));
+                }
+                cells.push(cell(c).ok_or_else(|| error(y, x, line))?);
+                len += 1;
+            }
+            if width.is_some_and(|w| len != w) {
+                return Err(error(y, len, line));
+            }
+            width = Some(len);
+            height += 1;
+        }
+        Ok(Grid {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, (x, y): Pos) -> Option<&T> {
+        if x < self.width && y < self.height {
+            Some(&self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, (x, y): Pos) -> Option<&mut T> {
+        if x < self.width && y < self.height {
+            Some(&mut self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    /// The cell at `(x, y)` in a plane tiled with copies of the grid.
+    pub fn get_wrapping(&self, (x, y): (isize, isize)) -> &T {
+        let x = x.rem_euclid(self.width as isize) as usize;
+        let y = y.rem_euclid(self.height as isize) as usize;
+        &self[(x, y)]
+    }
+
+    /// The cell nearest to `(x, y)`, which may be off the grid.
+    pub fn get_clamped(&self, (x, y): (isize, isize)) -> &T {
+        let x = x.clamp(0, self.width as isize - 1) as usize;
+        let y = y.clamp(0, self.height as isize - 1) as usize;
+        &self[(x, y)]
+    }
+
+    /// `pos` moved by `step`, if that is still on the grid.
+    pub fn offset(&self, (x, y): Pos, (dx, dy): (isize, isize)) -> Option<Pos> {
+        let x = x.checked_add_signed(dx).filter(|x| *x < self.width)?;
+        let y = y.checked_add_signed(dy).filter(|y| *y < self.height)?;
+        Some((x, y))
+    }
+
+    /// All positions, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let width = self.width;
+        (0..self.cells.len()).map(move |i| (i % width, i / width))
+    }
+
+    /// All positions and their cells, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// The orthogonal neighbours of `pos` on the grid.
+    pub fn neighbours4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        NEIGHBOURS4
+            .iter()
+            .filter_map(move |step| self.offset(pos, *step))
+    }
+
+    /// All neighbours of `pos` on the grid, diagonals included.
+    pub fn neighbours8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        NEIGHBOURS8
+            .iter()
+            .filter_map(move |step| self.offset(pos, *step))
+    }
+
+    /// The cells seen looking from `pos` in the direction of `step`, up to
+    /// the edge of the grid, `pos` itself not included.
+    pub fn ray(&self, pos: Pos, step: (isize, isize)) -> impl Iterator<Item = (Pos, &T)> {
+        std::iter::successors(self.offset(pos, step), move |p| self.offset(*p, step))
+            .map(move |p| (p, &self[p]))
+    }
+
+    /// The grid turned a quarter clockwise.
+    pub fn rotated(&self) -> Self
+    where
+        T: Clone,
+    {
+        let height = self.height;
+        Grid::from_fn(height, self.width, |(x, y)| {
+            self[(y, height - 1 - x)].clone()
+        })
+    }
+
+    /// The grid mirrored left to right.
+    pub fn flipped(&self) -> Self
+    where
+        T: Clone,
+    {
+        let width = self.width;
+        Grid::from_fn(width, self.height, |(x, y)| {
+            self[(width - 1 - x, y)].clone()
+        })
+    }
+
+    /// The grid in all 8 orientations: turned 0 to 3 quarters clockwise,
+    /// then the same mirrored.
+    pub fn orientations(&self) -> Vec<Self>
+    where
+        T: Clone,
+    {
+        let turns = |grid: Self| std::iter::successors(Some(grid), |g| Some(g.rotated())).take(4);
+        turns(self.clone()).chain(turns(self.flipped())).collect()
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Pos) -> &T {
+        self.get(pos)
+            .unwrap_or_else(|| panic!("{:?} is off the grid", pos))
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, pos: Pos) -> &mut T {
+        self.get_mut(pos)
+            .unwrap_or_else(|| panic!("{:?} is off the grid", pos))
+    }
+}
+
+/// The grid as text, one line per row, in the format `parse` reads when
+/// each cell is written as a single char.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (y, row) in self.cells.chunks(self.width.max(1)).enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// xorshift64, enough to make up inputs for tests.
+#[cfg(test)]
+pub(crate) struct Rng(pub u64);
+
+#[cfg(test)]
+impl Rng {
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number in `0..n`.
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -42,4 +339,87 @@ mod tests {
         use super::*;
         assert_eq!(uval("123"), Ok(("", 123)));
     }
+
+    #[test]
+    fn helper_parse_all() {
+        use super::*;
+        use nom::character::complete::newline;
+        use nom::multi::separated_list1;
+
+        let list = |i| separated_list1(newline, uval::<u32>)(i);
+        assert_eq!(parse_all("1\n2\n", list).unwrap(), vec![1, 2]);
+
+        let err = parse_day(3, "1\n2\nx3\n", list).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "day 3, line 3, column 1: cannot parse \"x3\""
+        );
+
+        let err = parse_all("12\n3-4", list).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Parse {
+                day: None,
+                line: 2,
+                column: 2,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn helper_grid() {
+        let grid = super::Grid::parse("#..\n.#.", |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })
+        .unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert!(grid[(1, 1)]);
+        assert_eq!(grid.get((3, 0)), None);
+        assert!(*grid.get_wrapping((3, -2)));
+        assert!(*grid.get_clamped((-5, 0)));
+        assert!(!*grid.get_clamped((7, 9)));
+
+        assert_eq!(
+            grid.neighbours4((0, 0)).collect::<Vec<_>>(),
+            [(1, 0), (0, 1)]
+        );
+        assert_eq!(grid.neighbours8((1, 0)).count(), 5);
+        let ray = grid.ray((0, 0), (1, 0)).map(|(p, _)| p);
+        assert_eq!(ray.collect::<Vec<_>>(), [(1, 0), (2, 0)]);
+        assert_eq!(grid.ray((0, 0), (-1, 1)).count(), 0);
+
+        let show = |g: &super::Grid<bool>| g.map(|c| if *c { '#' } else { '.' }).to_string();
+        assert_eq!(show(&grid.rotated()), ".#\n#.\n..");
+        assert_eq!(show(&grid.flipped()), "..#\n.#.");
+        let orientations = grid.orientations();
+        assert_eq!(orientations.len(), 8);
+        assert_eq!(orientations[2], grid.rotated().rotated());
+        assert_eq!(orientations[4], grid.flipped());
+        assert_eq!(grid.rotated().rotated().rotated().rotated(), grid);
+        assert_eq!(super::Grid::from_fn(3, 2, |(x, y)| x == y), grid);
+
+        let text = grid.map(|t| if *t { '#' } else { '.' }).to_string();
+        assert_eq!(text, "#..\n.#.");
+    }
+
+    #[test]
+    fn helper_grid_parse_error() {
+        let error = |input| super::Grid::parse(input, Some).unwrap_err().to_string();
+        assert_eq!(
+            error("abc\nabcd"),
+            "line 2, column 4: cannot parse \"abcd\""
+        );
+        assert_eq!(
+            error("abc\nab\nabc"),
+            "line 2, column 3: cannot parse \"ab\""
+        );
+        let error = super::Grid::parse("..\n.x", |c| Some(c).filter(|c| *c == '.'));
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "line 2, column 2: cannot parse \".x\""
+        );
+    }
 }
@@ -0,0 +1,267 @@
+//! Grids of hexagons with a pointy top, so that each has neighbours east
+//! and west, and north-east, north-west, south-east and south-west of it.
+//!
+//! Positions are in cube coordinates `q`, `r` and `s`, which add up to 0:
+//! going east adds 1 to `q` and takes 1 from `s`, going south-east adds 1
+//! to `r` and takes 1 from `s`, and so on. Every step changes two of them
+//! by 1, which makes distances and rotations simple.
+
+use nom::{branch::alt, bytes::complete::tag, combinator::map, multi::many1, IResult};
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// The six directions, counterclockwise from east.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    E,
+    NE,
+    NW,
+    W,
+    SW,
+    SE,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 6] = [
+        Direction::E,
+        Direction::NE,
+        Direction::NW,
+        Direction::W,
+        Direction::SW,
+        Direction::SE,
+    ];
+
+    /// The position one step from the origin this way.
+    pub fn step(self) -> Cube {
+        match self {
+            Direction::E => Cube::new(1, 0),
+            Direction::NE => Cube::new(1, -1),
+            Direction::NW => Cube::new(0, -1),
+            Direction::W => Cube::new(-1, 0),
+            Direction::SW => Cube::new(-1, 1),
+            Direction::SE => Cube::new(0, 1),
+        }
+    }
+
+    /// Turned counterclockwise by `sixths` sixths of a full turn, clockwise
+    /// if negative.
+    pub fn rotate(self, sixths: i64) -> Direction {
+        let idx = (self as i64 + sixths).rem_euclid(6);
+        Direction::ALL[idx as usize]
+    }
+
+    pub fn opposite(self) -> Direction {
+        self.rotate(3)
+    }
+
+    /// Mirrored in the east-west line.
+    pub fn reflect(self) -> Direction {
+        Direction::ALL[(6 - self as usize) % 6]
+    }
+}
+
+/// A position in cube coordinates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Cube {
+    q: i64,
+    r: i64,
+}
+
+impl Cube {
+    pub const ORIGIN: Cube = Cube { q: 0, r: 0 };
+
+    /// The position with the given `q` and `r`, and `s` making them up to 0.
+    pub fn new(q: i64, r: i64) -> Self {
+        Cube { q, r }
+    }
+
+    pub fn q(self) -> i64 {
+        self.q
+    }
+
+    pub fn r(self) -> i64 {
+        self.r
+    }
+
+    pub fn s(self) -> i64 {
+        -self.q - self.r
+    }
+
+    /// Where following `directions` from here leads.
+    pub fn walk<'a>(self, directions: impl IntoIterator<Item = &'a Direction>) -> Cube {
+        directions.into_iter().fold(self, |pos, d| pos + *d)
+    }
+
+    pub fn neighbours(self) -> impl Iterator<Item = Cube> {
+        Direction::ALL.iter().map(move |d| self + *d)
+    }
+
+    /// The number of steps between here and `other`.
+    pub fn distance(self, other: Cube) -> i64 {
+        let d = self - other;
+        (d.q.abs() + d.r.abs() + d.s().abs()) / 2
+    }
+
+    /// The positions `radius` steps from here, counterclockwise from the
+    /// one south-west.
+    pub fn ring(self, radius: i64) -> Vec<Cube> {
+        if radius == 0 {
+            return vec![self];
+        }
+        let mut pos = self + Direction::SW.step() * radius;
+        let mut ring = Vec::new();
+        for d in Direction::ALL.iter() {
+            for _ in 0..radius {
+                ring.push(pos);
+                pos = pos + *d;
+            }
+        }
+        ring
+    }
+
+    /// Turned counterclockwise around the origin by `sixths` sixths of a
+    /// full turn, clockwise if negative.
+    pub fn rotate(self, sixths: i64) -> Cube {
+        (0..sixths.rem_euclid(6)).fold(self, |c, _| Cube::new(-c.s(), -c.q))
+    }
+
+    /// Mirrored in the east-west line through the origin.
+    pub fn reflect(self) -> Cube {
+        Cube::new(self.q + self.r, -self.r)
+    }
+
+    /// The position in rows of hexagons, odd rows shifted half a hexagon
+    /// east of even ones. Row 0, column 0 is the origin.
+    pub fn to_offset(self) -> Offset {
+        Offset {
+            row: self.r,
+            col: self.q + (self.r - (self.r & 1)) / 2,
+        }
+    }
+}
+
+/// A position as a row and column, see `Cube::to_offset`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Offset {
+    pub row: i64,
+    pub col: i64,
+}
+
+impl From<Offset> for Cube {
+    fn from(offset: Offset) -> Self {
+        let Offset { row, col } = offset;
+        Cube::new(col - (row - (row & 1)) / 2, row)
+    }
+}
+
+impl Add for Cube {
+    type Output = Cube;
+
+    fn add(self, other: Cube) -> Cube {
+        Cube::new(self.q + other.q, self.r + other.r)
+    }
+}
+
+impl Add<Direction> for Cube {
+    type Output = Cube;
+
+    fn add(self, d: Direction) -> Cube {
+        self + d.step()
+    }
+}
+
+impl Sub for Cube {
+    type Output = Cube;
+
+    fn sub(self, other: Cube) -> Cube {
+        self + -other
+    }
+}
+
+impl Neg for Cube {
+    type Output = Cube;
+
+    fn neg(self) -> Cube {
+        Cube::new(-self.q, -self.r)
+    }
+}
+
+impl Mul<i64> for Cube {
+    type Output = Cube;
+
+    fn mul(self, n: i64) -> Cube {
+        Cube::new(self.q * n, self.r * n)
+    }
+}
+
+/// Directions written without separators, like `esenee`.
+pub fn directions(i: &str) -> IResult<&str, Vec<Direction>> {
+    let nw = map(tag("nw"), |_| Direction::NW);
+    let ne = map(tag("ne"), |_| Direction::NE);
+    let sw = map(tag("sw"), |_| Direction::SW);
+    let se = map(tag("se"), |_| Direction::SE);
+    let w = map(tag("w"), |_| Direction::W);
+    let e = map(tag("e"), |_| Direction::E);
+
+    many1(alt((nw, ne, sw, se, w, e)))(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cube, Direction, Offset};
+
+    #[test]
+    fn hex_directions() {
+        let (rest, dirs) = super::directions("nwwswee\n").unwrap();
+        assert_eq!(rest, "\n");
+        assert_eq!(Cube::ORIGIN.walk(&dirs), Cube::ORIGIN);
+        let (_, dirs) = super::directions("esew").unwrap();
+        assert_eq!(Cube::ORIGIN.walk(&dirs), Direction::SE.step());
+
+        assert_eq!(Direction::E.rotate(1), Direction::NE);
+        assert_eq!(Direction::E.rotate(-1), Direction::SE);
+        assert_eq!(Direction::NW.opposite(), Direction::SE);
+        assert_eq!(Direction::NE.reflect(), Direction::SE);
+        assert_eq!(Direction::W.reflect(), Direction::W);
+        for d in Direction::ALL.iter() {
+            let step = d.step();
+            assert_eq!(step.q() + step.r() + step.s(), 0);
+            assert_eq!(step.rotate(2), d.rotate(2).step());
+            assert_eq!(step.reflect(), d.reflect().step());
+            assert_eq!(-step, d.opposite().step());
+        }
+    }
+
+    #[test]
+    fn hex_distance_and_rings() {
+        let pos = Cube::new(2, -1);
+        assert_eq!(pos.distance(Cube::ORIGIN), 2);
+        assert_eq!(pos.neighbours().count(), 6);
+        assert!(pos.neighbours().all(|n| n.distance(pos) == 1));
+        assert_eq!(pos.ring(0), vec![pos]);
+        for radius in 1..4 {
+            let ring = pos.ring(radius);
+            assert_eq!(ring.len(), 6 * radius as usize);
+            assert!(ring.iter().all(|c| c.distance(pos) == radius));
+        }
+        assert_eq!(pos.rotate(6), pos);
+        assert_eq!(pos.rotate(3), -pos);
+        assert_eq!(pos.rotate(-1), pos.rotate(5));
+        assert_eq!(pos.reflect().reflect(), pos);
+    }
+
+    #[test]
+    fn hex_offset() {
+        // Odd rows are shifted east, so north-east of row 0 is the same
+        // column and north-east of row 1 the next one
+        let ne = |row, col| Cube::from(Offset { row, col }) + Direction::NE;
+        assert_eq!(ne(0, 0).to_offset(), Offset { row: -1, col: 0 });
+        assert_eq!(ne(1, 0).to_offset(), Offset { row: 0, col: 1 });
+        assert_eq!(ne(-1, 3).to_offset(), Offset { row: -2, col: 4 });
+        for row in -3..3 {
+            for col in -3..3 {
+                let offset = Offset { row, col };
+                assert_eq!(Cube::from(offset).to_offset(), offset);
+            }
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::error::{Error, Result};
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// Environment variable used for the inputs directory when `--inputs-dir`
+/// isn't given.
+pub const INPUTS_DIR_VAR: &str = "AOC_INPUTS_DIR";
+
+/// Where to read an explicitly given input from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Source {
+    Path(PathBuf),
+    Stdin,
+}
+
+impl Source {
+    /// `-` means stdin, anything else is a path.
+    pub fn from_arg(arg: &str) -> Self {
+        if arg == "-" {
+            Source::Stdin
+        } else {
+            Source::Path(PathBuf::from(arg))
+        }
+    }
+}
+
+/// Resolves the puzzle input for a day. An explicit source wins, otherwise
+/// the input is `dayN.txt` in the inputs directory.
+#[derive(Clone, Debug)]
+pub struct Inputs {
+    dir: PathBuf,
+    source: Option<Source>,
+}
+
+impl Inputs {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Inputs {
+            dir: dir.into(),
+            source: None,
+        }
+    }
+
+    /// Uses `$AOC_INPUTS_DIR`, or the current directory if it isn't set.
+    pub fn from_env() -> Self {
+        Self::new(env::var_os(INPUTS_DIR_VAR).unwrap_or_else(|| ".".into()))
+    }
+
+    pub fn with_source(mut self, source: Source) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn path_for(&self, day: usize) -> PathBuf {
+        match &self.source {
+            Some(Source::Path(path)) => path.clone(),
+            _ => self.dir.join(format!("day{}.txt", day)),
+        }
+    }
+
+    pub fn load(&self, day: usize) -> Result<String> {
+        if let Some(Source::Stdin) = self.source {
+            let mut input = String::new();
+            return io::stdin()
+                .read_to_string(&mut input)
+                .map(|_| input)
+                .map_err(|source| Error::Io {
+                    path: PathBuf::from("<stdin>"),
+                    source,
+                });
+        }
+
+        let path = self.path_for(day);
+        fs::read_to_string(&path).map_err(|source| match source.kind() {
+            io::ErrorKind::NotFound => Error::MissingInput { day, path },
+            _ => Error::Io { path, source },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    #[test]
+    fn input_paths() {
+        let inputs = super::Inputs::new("inputs");
+        assert_eq!(inputs.path_for(7), PathBuf::from("inputs/day7.txt"));
+
+        let inputs = inputs.with_source(super::Source::from_arg("other.txt"));
+        assert_eq!(inputs.path_for(7), PathBuf::from("other.txt"));
+
+        assert_eq!(super::Source::from_arg("-"), super::Source::Stdin);
+    }
+
+    #[test]
+    fn input_load() {
+        let dir = std::env::temp_dir().join(format!("aoc-inputs-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("day1.txt"), "1\n2\n").unwrap();
+
+        let inputs = super::Inputs::new(&dir);
+        assert_eq!(inputs.load(1).unwrap(), "1\n2\n");
+
+        let err = inputs.load(2).unwrap_err();
+        assert!(err.to_string().contains("day2.txt"));
+        assert!(matches!(
+            err,
+            crate::error::Error::MissingInput { day: 2, .. }
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,20 +1,104 @@
-use nom::character::complete::newline;
+//! The VM for the 2020 handheld console programs (day 8) and for 2019
+//! Intcode. Both run on the same `CPU`, they only differ in where
+//! instructions come from. Handheld programs are a list of `Op`s indexed by
+//! `pc`, while Intcode is decoded from `memory` at `pc` and may rewrite
+//! itself.
 
-#[derive(Clone, Debug, PartialEq)]
+use crate::error::Result;
+use nom::character::complete::{char, multispace0, newline};
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::fmt;
+
+/// Writes beyond this many words of memory fault instead of growing it.
+pub const MEMORY_LIMIT: usize = 1 << 24;
+
+/// An Intcode operand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Param {
+    /// The value at an address.
+    Position(i64),
+    Immediate(i64),
+    /// The value at an address relative to the relative base.
+    Relative(i64),
+}
+
+impl fmt::Display for Param {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Param::Position(a) => write!(f, "[{}]", a),
+            Param::Immediate(v) => write!(f, "{}", v),
+            Param::Relative(o) => write!(f, "[rb{:+}]", o),
+        }
+    }
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Op {
     Nop(i64),
     Acc(i64),
+    /// Relative jump.
     Jmp(i64),
-    // Add,
-    // Mul,
-    // Store,
-    // Load,
-    // JumpIfTrue,
-    // JumpIfFalse,
-    // LT,
-    // Eq,
-    // AdjRelBase,
-    // End,
+    Add(Param, Param, Param),
+    Mul(Param, Param, Param),
+    /// Stores the next input.
+    Store(Param),
+    /// Loads a value onto the output.
+    Load(Param),
+    /// Jumps to the second operand if the first isn't zero.
+    JumpIfTrue(Param, Param),
+    /// Jumps to the second operand if the first is zero.
+    JumpIfFalse(Param, Param),
+    LT(Param, Param, Param),
+    Eq(Param, Param, Param),
+    AdjRelBase(Param),
+    End,
+    /// A word of Intcode memory that isn't a valid instruction.
+    Data(i64),
+}
+
+impl Op {
+    /// The name of the instruction, as in its text form.
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Op::Nop(_) => "nop",
+            Op::Acc(_) => "acc",
+            Op::Jmp(_) => "jmp",
+            Op::Add(..) => "add",
+            Op::Mul(..) => "mul",
+            Op::Store(_) => "store",
+            Op::Load(_) => "load",
+            Op::JumpIfTrue(..) => "jt",
+            Op::JumpIfFalse(..) => "jf",
+            Op::LT(..) => "lt",
+            Op::Eq(..) => "eq",
+            Op::AdjRelBase(_) => "arb",
+            Op::End => "end",
+            Op::Data(_) => "data",
+        }
+    }
+}
+
+impl fmt::Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Op::Nop(v) => write!(f, "nop {:+}", v),
+            Op::Acc(v) => write!(f, "acc {:+}", v),
+            Op::Jmp(v) => write!(f, "jmp {:+}", v),
+            Op::Add(a, b, c) => write!(f, "add {}, {}, {}", a, b, c),
+            Op::Mul(a, b, c) => write!(f, "mul {}, {}, {}", a, b, c),
+            Op::Store(a) => write!(f, "store {}", a),
+            Op::Load(a) => write!(f, "load {}", a),
+            Op::JumpIfTrue(a, b) => write!(f, "jt {}, {}", a, b),
+            Op::JumpIfFalse(a, b) => write!(f, "jf {}, {}", a, b),
+            Op::LT(a, b, c) => write!(f, "lt {}, {}, {}", a, b, c),
+            Op::Eq(a, b, c) => write!(f, "eq {}, {}, {}", a, b, c),
+            Op::AdjRelBase(a) => write!(f, "arb {}", a),
+            Op::End => write!(f, "end"),
+            Op::Data(v) => write!(f, "data {}", v),
+        }
+    }
 }
 
 fn nop(i: &str) -> nom::IResult<&str, Op> {
@@ -38,54 +122,362 @@ fn operation(i: &str) -> nom::IResult<&str, Op> {
     Ok((i, op))
 }
 
-fn parse_program(i: &str) -> nom::IResult<&str, Vec<Op>> {
+pub fn parse_program(i: &str) -> nom::IResult<&str, Vec<Op>> {
     nom::multi::separated_list1(newline, operation)(i)
 }
 
-#[derive(Clone)]
+/// Comma separated Intcode.
+pub fn parse_intcode(i: &str) -> nom::IResult<&str, Vec<i64>> {
+    let comma = nom::sequence::tuple((multispace0, char(','), multispace0));
+    nom::multi::separated_list1(comma, crate::helper::ival)(i)
+}
+
+/// Decodes the Intcode instruction at `addr`, returning it and its length
+/// in words. Anything that isn't a valid instruction is a single word of
+/// `Op::Data`.
+pub fn decode(memory: &[i64], addr: usize) -> (Op, usize) {
+    let word = |i: usize| memory.get(addr + i).copied().unwrap_or(0);
+    let value = word(0);
+    let param = |i: usize| match (value / 10i64.pow(i as u32 + 1)) % 10 {
+        0 => Some(Param::Position(word(i))),
+        1 => Some(Param::Immediate(word(i))),
+        2 => Some(Param::Relative(word(i))),
+        _ => None,
+    };
+    // Parameters that are written to can't be immediate
+    let target = |i: usize| param(i).filter(|p| !matches!(p, Param::Immediate(_)));
+
+    let decoded = || {
+        if value < 0 {
+            return None;
+        }
+        Some(match value % 100 {
+            1 => (Op::Add(param(1)?, param(2)?, target(3)?), 4),
+            2 => (Op::Mul(param(1)?, param(2)?, target(3)?), 4),
+            3 => (Op::Store(target(1)?), 2),
+            4 => (Op::Load(param(1)?), 2),
+            5 => (Op::JumpIfTrue(param(1)?, param(2)?), 3),
+            6 => (Op::JumpIfFalse(param(1)?, param(2)?), 3),
+            7 => (Op::LT(param(1)?, param(2)?, target(3)?), 4),
+            8 => (Op::Eq(param(1)?, param(2)?, target(3)?), 4),
+            9 => (Op::AdjRelBase(param(1)?), 2),
+            99 => (Op::End, 1),
+            _ => return None,
+        })
+    };
+    decoded().unwrap_or((Op::Data(value), 1))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FaultKind {
+    /// A jump to before the first instruction.
+    NegativeJump,
+    /// A jump past the instruction right after the last one.
+    JumpOutOfRange,
+    AccOverflow,
+    /// An `Add` or `Mul` that overflowed, or a relative base that did.
+    ArithmeticOverflow,
+    NegativeAddress,
+    /// An address at or beyond `MEMORY_LIMIT`.
+    AddressOutOfRange,
+    /// `Op::Data`, or a write to an immediate operand.
+    InvalidInstruction,
+}
+
+/// An instruction that couldn't be executed. The CPU is left as it was
+/// before it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fault {
+    pub pc: usize,
+    pub op: Op,
+    pub kind: FaultKind,
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let what = match self.kind {
+            FaultKind::NegativeJump => "jump to a negative address",
+            FaultKind::JumpOutOfRange => "jump out of the program",
+            FaultKind::AccOverflow => "accumulator overflow",
+            FaultKind::ArithmeticOverflow => "arithmetic overflow",
+            FaultKind::NegativeAddress => "negative address",
+            FaultKind::AddressOutOfRange => "address out of range",
+            FaultKind::InvalidInstruction => "invalid instruction",
+        };
+        write!(f, "{} at pc {} ({})", what, self.pc, self.op)
+    }
+}
+
+impl std::error::Error for Fault {}
+
+/// What a single `CPU::step` did.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Step {
+    Executed,
+    /// Nothing, the program has ended.
+    Halted,
+    /// Nothing, the instruction at `pc` needs input and there is none.
+    NeedsInput,
+}
+
+/// How a program run by `CPU::run` ended.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Termination {
+    /// Ran past the last instruction, or reached `End`. This is the normal
+    /// way for a program to end.
+    Halted { acc: i64 },
+    /// Was about to execute the instruction at `pc` a second time.
+    InfiniteLoop { pc: usize, acc: i64 },
+    /// Hit an instruction it couldn't execute, the program is corrupt.
+    Faulted(Fault),
+    /// Waits for input at `pc`. Push some and run again to continue.
+    NeedsInput { pc: usize },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Isa {
+    Handheld,
+    Intcode,
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Debug)]
 pub struct CPU {
     pub pc: usize,
+    /// The instructions of a handheld program, empty for Intcode.
     pub code: Vec<Op>,
     pub acc: i64,
+    /// Intcode memory, holding its program. Reads beyond the end are zero
+    /// and writes grow it.
+    pub memory: Vec<i64>,
+    pub relative_base: i64,
+    pub input: VecDeque<i64>,
+    pub output: VecDeque<i64>,
+    isa: Isa,
 }
 
 impl CPU {
-    pub fn new(program: &str) -> Self {
-        let (_, code) = parse_program(program).unwrap();
+    fn with_isa(isa: Isa, code: Vec<Op>, memory: Vec<i64>) -> Self {
         CPU {
             pc: 0,
             code,
             acc: 0,
+            memory,
+            relative_base: 0,
+            input: VecDeque::new(),
+            output: VecDeque::new(),
+            isa,
+        }
+    }
+
+    /// A CPU running a handheld console program.
+    pub fn new(program: &str) -> Result<Self> {
+        let code = crate::helper::parse_all(program, parse_program)?;
+        Ok(Self::from_code(code))
+    }
+
+    /// A CPU running an already parsed handheld program.
+    pub fn from_code(code: Vec<Op>) -> Self {
+        Self::with_isa(Isa::Handheld, code, Vec::new())
+    }
+
+    /// A CPU running a comma separated Intcode program.
+    pub fn intcode(program: &str) -> Result<Self> {
+        let memory = crate::helper::parse_all(program, parse_intcode)?;
+        Ok(Self::from_memory(memory))
+    }
+
+    /// A CPU running Intcode already in memory.
+    pub fn from_memory(memory: Vec<i64>) -> Self {
+        Self::with_isa(Isa::Intcode, Vec::new(), memory)
+    }
+
+    /// Whether this runs a handheld program rather than Intcode.
+    pub fn is_handheld(&self) -> bool {
+        self.isa == Isa::Handheld
+    }
+
+    /// The instruction at `pc` and its length, or `None` past the end of a
+    /// handheld program.
+    pub fn fetch(&self) -> Option<(Op, usize)> {
+        match self.isa {
+            Isa::Handheld => self.code.get(self.pc).map(|op| (*op, 1)),
+            Isa::Intcode => Some(decode(&self.memory, self.pc)),
+        }
+    }
+
+    fn address(&self, param: Param) -> std::result::Result<usize, FaultKind> {
+        let addr = match param {
+            Param::Position(addr) => addr,
+            Param::Relative(offset) => self
+                .relative_base
+                .checked_add(offset)
+                .ok_or(FaultKind::AddressOutOfRange)?,
+            Param::Immediate(_) => return Err(FaultKind::InvalidInstruction),
+        };
+        let addr = usize::try_from(addr).map_err(|_| FaultKind::NegativeAddress)?;
+        if addr >= MEMORY_LIMIT {
+            return Err(FaultKind::AddressOutOfRange);
+        }
+        Ok(addr)
+    }
+
+    fn read(&self, param: Param) -> std::result::Result<i64, FaultKind> {
+        match param {
+            Param::Immediate(v) => Ok(v),
+            _ => Ok(self.memory.get(self.address(param)?).copied().unwrap_or(0)),
+        }
+    }
+
+    fn write(&mut self, addr: usize, value: i64) {
+        if addr >= self.memory.len() {
+            self.memory.resize(addr + 1, 0);
         }
+        self.memory[addr] = value;
     }
 
-    pub fn step(&mut self) -> Option<()> {
-        // use nom::lib::std::prelude::v1::v1::stringify;
-        if let Some(op) = self.code.get(self.pc) {
-            let res = match op {
-                Op::Nop(_) => {
-                    self.pc += 1;
+    /// Executes the instruction at `pc`.
+    pub fn step(&mut self) -> std::result::Result<Step, Fault> {
+        match self.fetch() {
+            Some((op, len)) => self.execute(op, len),
+            None => Ok(Step::Halted),
+        }
+    }
+
+    /// Executes `op`, fetched from `pc` and `len` words long.
+    fn execute(&mut self, op: Op, len: usize) -> std::result::Result<Step, Fault> {
+        let pc = self.pc;
+        let fault = |kind| Fault { pc, op, kind };
+        // Check everything that can fault before changing any state
+        let arith = |a: Param, b: Param, f: fn(i64, i64) -> Option<i64>| {
+            let v = f(self.read(a)?, self.read(b)?).ok_or(FaultKind::ArithmeticOverflow)?;
+            Ok::<_, FaultKind>(v)
+        };
+        let jump = |cond: Param, target: Param, when: bool| {
+            if (self.read(cond)? != 0) != when {
+                return Ok(pc + len);
+            }
+            usize::try_from(self.read(target)?).map_err(|_| FaultKind::NegativeJump)
+        };
+        match op {
+            Op::Nop(_) => {
+                self.pc += 1;
+            }
+            Op::Acc(a) => {
+                self.acc = self
+                    .acc
+                    .checked_add(a)
+                    .ok_or_else(|| fault(FaultKind::AccOverflow))?;
+                self.pc += 1;
+            }
+            Op::Jmp(a) => {
+                let target = i64::try_from(self.pc)
+                    .ok()
+                    .and_then(|pc| pc.checked_add(a))
+                    .ok_or_else(|| fault(FaultKind::JumpOutOfRange))?;
+                let target = usize::try_from(target).map_err(|_| fault(FaultKind::NegativeJump))?;
+                if target > self.code.len() {
+                    return Err(fault(FaultKind::JumpOutOfRange));
                 }
-                Op::Acc(a) => {
-                    self.acc += *a;
-                    self.pc += 1;
+                self.pc = target;
+            }
+            Op::Add(a, b, c) | Op::Mul(a, b, c) | Op::LT(a, b, c) | Op::Eq(a, b, c) => {
+                let f: fn(i64, i64) -> Option<i64> = match op {
+                    Op::Add(..) => i64::checked_add,
+                    Op::Mul(..) => i64::checked_mul,
+                    Op::LT(..) => |a, b| Some((a < b) as i64),
+                    _ => |a, b| Some((a == b) as i64),
+                };
+                let value = arith(a, b, f).map_err(fault)?;
+                let addr = self.address(c).map_err(fault)?;
+                self.write(addr, value);
+                self.pc += len;
+            }
+            Op::Store(a) => {
+                let addr = self.address(a).map_err(fault)?;
+                match self.input.pop_front() {
+                    Some(value) => self.write(addr, value),
+                    None => return Ok(Step::NeedsInput),
                 }
-                Op::Jmp(a) => {
-                    self.pc = (self.pc as i64 + *a) as usize;
+                self.pc += len;
+            }
+            Op::Load(a) => {
+                let value = self.read(a).map_err(fault)?;
+                self.output.push_back(value);
+                self.pc += len;
+            }
+            Op::JumpIfTrue(cond, target) => self.pc = jump(cond, target, true).map_err(fault)?,
+            Op::JumpIfFalse(cond, target) => self.pc = jump(cond, target, false).map_err(fault)?,
+            Op::AdjRelBase(a) => {
+                let offset = self.read(a).map_err(fault)?;
+                self.relative_base = self
+                    .relative_base
+                    .checked_add(offset)
+                    .ok_or_else(|| fault(FaultKind::ArithmeticOverflow))?;
+                self.pc += len;
+            }
+            Op::End => return Ok(Step::Halted),
+            Op::Data(_) => return Err(fault(FaultKind::InvalidInstruction)),
+        };
+        Ok(Step::Executed)
+    }
+
+    /// Runs until the program ends, faults or needs input. A handheld
+    /// program that would execute any instruction twice is stopped, since
+    /// it can't branch on state that means it would loop forever. Intcode
+    /// can, so there's no such check for it.
+    pub fn run(&mut self) -> Termination {
+        self.run_with(|_, _, _, _| {})
+    }
+
+    /// `run`, calling `on_step` after each instruction executed with its pc,
+    /// the instruction, acc before it and the CPU after it.
+    pub fn run_with<F: FnMut(usize, Op, i64, &CPU)>(&mut self, mut on_step: F) -> Termination {
+        let mut visited = match self.isa {
+            Isa::Handheld => vec![false; self.code.len()],
+            Isa::Intcode => Vec::new(),
+        };
+        for steps in 1u64.. {
+            if let Some(seen) = visited.get_mut(self.pc) {
+                if *seen {
+                    return Termination::InfiniteLoop {
+                        pc: self.pc,
+                        acc: self.acc,
+                    };
                 }
+                *seen = true;
+            }
+            if steps.is_multiple_of(1 << 16) {
+                crate::runner::checkpoint();
+            }
+            let (op, len) = match self.fetch() {
+                Some(fetched) => fetched,
+                None => return Termination::Halted { acc: self.acc },
             };
-            // dbg! {(self.pc, &op)};
-            Some(res)
-        } else {
-            None
+            let (pc, acc) = (self.pc, self.acc);
+            match self.execute(op, len) {
+                Ok(Step::Executed) => on_step(pc, op, acc, self),
+                Ok(Step::Halted) => return Termination::Halted { acc: self.acc },
+                Ok(Step::NeedsInput) => return Termination::NeedsInput { pc: self.pc },
+                Err(fault) => return Termination::Faulted(fault),
+            }
         }
+        unreachable!()
     }
-
-    // pub fn run(&mut self) {
-    //     while self.step().is_some() {}
-    // }
 }
 
+/// The example program of the day 8 puzzle, for tests.
+#[cfg(test)]
+pub(crate) const EXAMPLE: &str = "nop +0
+acc +1
+jmp +4
+acc +3
+jmp -3
+acc -99
+acc +1
+jmp -4
+acc +6";
+
 #[cfg(test)]
 mod tests {
 
@@ -111,17 +503,136 @@ mod tests {
 
     #[test]
     fn intcode_parse_program() {
-        const INPUT: &str = "nop +0
-acc +1
-jmp +4
-acc +3
-jmp -3
-acc -99
-acc +1
-jmp -4
-acc +6";
-        let (i, code) = super::parse_program(INPUT).unwrap();
+        let (i, code) = super::parse_program(super::EXAMPLE).unwrap();
         assert_eq!(i, "");
         assert_eq!(code.len(), 9);
     }
+
+    #[test]
+    fn intcode_run() {
+        use super::{Fault, FaultKind, Op, Termination, CPU};
+
+        let mut cpu = CPU::new(super::EXAMPLE).unwrap();
+        assert_eq!(cpu.run(), Termination::InfiniteLoop { pc: 1, acc: 5 });
+
+        let mut cpu = CPU::new("acc +1\njmp +2\nacc +10\nacc +2").unwrap();
+        assert_eq!(cpu.run(), Termination::Halted { acc: 3 });
+
+        let mut cpu = CPU::new("acc +1\njmp +3\nacc +2").unwrap();
+        assert_eq!(
+            cpu.run(),
+            Termination::Faulted(Fault {
+                pc: 1,
+                op: Op::Jmp(3),
+                kind: FaultKind::JumpOutOfRange
+            })
+        );
+    }
+
+    #[test]
+    fn intcode_faults() {
+        use super::{Fault, FaultKind, Op, Step, Termination, CPU};
+
+        let mut cpu = CPU::new("nop +0\njmp -2").unwrap();
+        assert_eq!(cpu.step(), Ok(Step::Executed));
+        let fault = cpu.step().unwrap_err();
+        assert_eq!(fault.kind, FaultKind::NegativeJump);
+        assert_eq!(
+            fault.to_string(),
+            "jump to a negative address at pc 1 (jmp -2)"
+        );
+        // The faulting instruction isn't executed
+        assert_eq!(cpu.pc, 1);
+
+        let mut cpu = CPU::new("acc +9223372036854775807\nacc +1").unwrap();
+        assert_eq!(cpu.step(), Ok(Step::Executed));
+        assert_eq!(
+            cpu.step(),
+            Err(Fault {
+                pc: 1,
+                op: Op::Acc(1),
+                kind: FaultKind::AccOverflow
+            })
+        );
+        assert_eq!(cpu.acc, i64::MAX);
+
+        // Jumping right past the end is how a program halts
+        let mut cpu = CPU::new("jmp +1").unwrap();
+        assert_eq!(cpu.step(), Ok(Step::Executed));
+        assert_eq!(cpu.step(), Ok(Step::Halted));
+
+        let mut cpu = CPU::intcode("1,0,0,-1,99").unwrap();
+        assert_eq!(cpu.step().unwrap_err().kind, FaultKind::NegativeAddress);
+        let mut cpu = CPU::intcode("1101,9223372036854775807,1,0,99").unwrap();
+        assert_eq!(cpu.step().unwrap_err().kind, FaultKind::ArithmeticOverflow);
+        let mut cpu = CPU::intcode("1105,1,-4").unwrap();
+        assert_eq!(cpu.step().unwrap_err().kind, FaultKind::NegativeJump);
+        let mut cpu = CPU::intcode("42").unwrap();
+        assert_eq!(
+            cpu.run(),
+            Termination::Faulted(Fault {
+                pc: 0,
+                op: Op::Data(42),
+                kind: FaultKind::InvalidInstruction
+            })
+        );
+    }
+
+    #[test]
+    fn intcode_decode() {
+        use super::{decode, Op, Param};
+
+        assert_eq!(
+            decode(&[1002, 4, 3, 4], 0),
+            (
+                Op::Mul(Param::Position(4), Param::Immediate(3), Param::Position(4)),
+                4
+            )
+        );
+        assert_eq!(decode(&[204, -1], 0), (Op::Load(Param::Relative(-1)), 2));
+        assert_eq!(decode(&[99], 0), (Op::End, 1));
+        // Writing to an immediate isn't allowed
+        assert_eq!(decode(&[11101, 1, 1, 0], 0), (Op::Data(11101), 1));
+        assert_eq!(decode(&[3, 0], 1), (Op::Data(0), 1));
+    }
+
+    #[test]
+    fn intcode_run_intcode() {
+        use super::{Termination, CPU};
+
+        let mut cpu = CPU::intcode("1,9,10,3,2,3,11,0,99,30,40,50").unwrap();
+        assert_eq!(cpu.run(), Termination::Halted { acc: 0 });
+        assert_eq!(cpu.memory[0], 3500);
+
+        // Outputs 1 if the input is 8, otherwise 0
+        let program = "3,9,8,9,10,9,4,9,99,-1,8";
+        for (input, output) in [(8, 1), (7, 0)] {
+            let mut cpu = CPU::intcode(program).unwrap();
+            assert_eq!(cpu.run(), Termination::NeedsInput { pc: 0 });
+            cpu.input.push_back(input);
+            assert_eq!(cpu.run(), Termination::Halted { acc: 0 });
+            assert_eq!(cpu.output, vec![output]);
+        }
+
+        // A quine, using the relative base
+        let quine = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+        let mut cpu = CPU::intcode(quine).unwrap();
+        assert_eq!(cpu.run(), Termination::Halted { acc: 0 });
+        let output = cpu.output.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        assert_eq!(output.join(","), quine);
+
+        let mut cpu = CPU::intcode("104,1125899906842624,99").unwrap();
+        cpu.run();
+        assert_eq!(cpu.output, vec![1125899906842624]);
+    }
+
+    #[test]
+    fn intcode_parse_error() {
+        let err = super::CPU::new("nop +0\nacc +1\nhcf +2").err().unwrap();
+        assert_eq!(err.to_string(), "line 3, column 1: cannot parse \"hcf +2\"");
+        assert_eq!(
+            err.for_day(8).to_string(),
+            "day 8, line 3, column 1: cannot parse \"hcf +2\""
+        );
+    }
 }
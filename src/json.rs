@@ -0,0 +1,94 @@
+//! Machine-readable output: one JSON object per line for each solved part:
+//!
+//! ```text
+//! {"day":1,"part":1,"answer":1007104,"duration":0.000123,"error":null}
+//! {"day":21,"part":2,"answer":"dpkvsdk,xmmpt","duration":0.0021,"error":null}
+//! {"day":23,"part":1,"answer":null,"duration":0.000001,"error":"day 23, part 1: not implemented yet"}
+//! ```
+//!
+//! `duration` is in seconds. `answer` is `null` for a failed part, or a part
+//! without a puzzle.
+
+use crate::runner::PartResult;
+use crate::solution::Answer;
+use std::fmt::Write;
+
+/// Quotes and escapes `s` as a JSON string.
+pub fn string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn answer(answer: &Answer) -> String {
+    match answer {
+        Answer::Int(v) => v.to_string(),
+        Answer::Str(s) => string(s),
+        Answer::None => "null".to_string(),
+    }
+}
+
+/// A single line record for `result`.
+pub fn record(result: &PartResult) -> String {
+    let (ans, error) = match &result.answer {
+        Ok(a) => (answer(a), "null".to_string()),
+        Err(e) => ("null".to_string(), string(&e.to_string())),
+    };
+    format!(
+        "{{\"day\":{},\"part\":{},\"answer\":{},\"duration\":{},\"error\":{}}}",
+        result.day,
+        result.part,
+        ans,
+        result.duration.as_secs_f64(),
+        error
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::runner::{Failure, PartResult};
+    use crate::solution::Answer;
+    use std::time::Duration;
+
+    #[test]
+    fn json_string() {
+        assert_eq!(super::string("abc"), "\"abc\"");
+        assert_eq!(
+            super::string("a \"b\"\\\n\u{1}"),
+            "\"a \\\"b\\\"\\\\\\n\\u0001\""
+        );
+    }
+
+    #[test]
+    fn json_record() {
+        let mut result = PartResult {
+            day: 21,
+            part: 2,
+            answer: Ok(Answer::Str("a,b".to_string())),
+            duration: Duration::from_millis(1500),
+        };
+        assert_eq!(
+            super::record(&result),
+            "{\"day\":21,\"part\":2,\"answer\":\"a,b\",\"duration\":1.5,\"error\":null}"
+        );
+
+        result.answer = Ok(Answer::Int(-3));
+        assert!(super::record(&result).contains("\"answer\":-3,"));
+
+        result.answer = Err(Failure::Error("bad \"input\"".to_string()));
+        assert!(super::record(&result)
+            .ends_with("\"answer\":null,\"duration\":1.5,\"error\":\"bad \\\"input\\\"\"}"));
+    }
+}
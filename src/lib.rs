@@ -0,0 +1,60 @@
+//! Advent of Code 2020 solutions as a library.
+//!
+//! Every day implements [`solution::Solution`], and [`solution::registry`]
+//! lists them all. The nom parsers and the `Grid` shared between days are
+//! in [`helper`], and the handheld console VM is in [`intcode`], with
+//! listings of its programs in [`asm`]. Days 11, 17 and 24 run on the
+//! cellular automaton engine in [`automaton`]. The binary is a thin CLI on
+//! top of [`runner`].
+//!
+//! ```
+//! use advent_of_code2019::aoc1::Day1;
+//! use advent_of_code2019::solution::{Answer, Solution};
+//!
+//! let input = Day1::parse("1721\n979\n366\n299\n675\n1456").unwrap();
+//! assert_eq!(Day1::part1(&input).unwrap(), Answer::Int(514579));
+//! ```
+
+pub mod analysis;
+pub mod answers;
+pub mod aoc1;
+pub mod aoc10;
+pub mod aoc11;
+pub mod aoc12;
+pub mod aoc13;
+pub mod aoc14;
+pub mod aoc15;
+pub mod aoc16;
+pub mod aoc17;
+pub mod aoc18;
+pub mod aoc19;
+pub mod aoc2;
+pub mod aoc20;
+pub mod aoc21;
+pub mod aoc22;
+pub mod aoc23;
+pub mod aoc24;
+pub mod aoc25;
+pub mod aoc3;
+pub mod aoc4;
+pub mod aoc5;
+pub mod aoc6;
+pub mod aoc7;
+pub mod aoc8;
+pub mod aoc9;
+pub mod asm;
+pub mod automaton;
+pub mod bench;
+pub mod debugger;
+pub mod error;
+pub mod helper;
+pub mod hex;
+pub mod input;
+pub mod intcode;
+pub mod json;
+pub mod mutate;
+pub mod runner;
+pub mod snapshot;
+pub mod solution;
+pub mod threaded;
+pub mod trace;
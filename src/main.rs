@@ -1,68 +1,389 @@
+use advent_of_code2019::answers::Answers;
+use advent_of_code2019::debugger::Debugger;
+use advent_of_code2019::input::{Inputs, Source};
+use advent_of_code2019::intcode::CPU;
+use advent_of_code2019::json;
+use advent_of_code2019::runner::{self, Failure, PartResult};
+use advent_of_code2019::solution;
 use std::env;
+use std::io::{self, BufRead, Write};
+use std::panic;
+use std::path::PathBuf;
+use std::process;
+use std::time::Duration;
 
-mod aoc1;
-mod aoc10;
-mod aoc11;
-mod aoc12;
-mod aoc13;
-mod aoc14;
-mod aoc15;
-mod aoc16;
-mod aoc17;
-mod aoc18;
-mod aoc19;
-mod aoc2;
-mod aoc20;
-mod aoc21;
-mod aoc22;
-mod aoc23;
-mod aoc24;
-mod aoc25;
-mod aoc3;
-mod aoc4;
-mod aoc5;
-mod aoc6;
-mod aoc7;
-mod aoc8;
-mod aoc9;
-mod helper;
-mod intcode;
+const USAGE: &str = "usage: advent_of_code2019 [options] <day>
+       advent_of_code2019 [options] all
+       advent_of_code2019 [options] verify
+       advent_of_code2019 [options] debug
 
-fn main() {
-    let mut a = env::args();
-    a.next();
+options:
+    --input <path>       read the input from <path>, or from stdin if <path> is -
+    --inputs-dir <dir>   read dayN.txt from <dir> (default $AOC_INPUTS_DIR or .)
+    --answers <path>     known answers for verify (default <inputs-dir>/answers.toml)
+    --format <format>    text (default) or json, one record per line for each part
+    --jobs <n>           solve up to <n> days at once with all and verify (default 1)
+    --timeout <secs>     give up on a day after <secs> seconds and report it as TIMEOUT
+
+debug steps through the day 8 console program, or the one given with --input,
+which can't be stdin. Type help at its prompt for the commands.";
+
+enum Command {
+    Day(usize),
+    All,
+    Verify,
+    Debug,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Text,
+    Json,
+}
+
+struct Options {
+    command: Command,
+    inputs: Inputs,
+    answers: PathBuf,
+    format: Format,
+    jobs: usize,
+    timeout: Option<Duration>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut command = None;
+    let mut source = None;
+    let mut inputs_dir = None;
+    let mut answers = None;
+    let mut format = Format::Text;
+    let mut jobs = 1;
+    let mut timeout = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => {
+                let path = args.next().ok_or("--input needs a path")?;
+                source = Some(Source::from_arg(&path));
+            }
+            "--inputs-dir" => {
+                inputs_dir = Some(args.next().ok_or("--inputs-dir needs a directory")?);
+            }
+            "--answers" => {
+                answers = Some(PathBuf::from(args.next().ok_or("--answers needs a path")?));
+            }
+            "--format" => {
+                format = match args.next().as_deref() {
+                    Some("text") => Format::Text,
+                    Some("json") => Format::Json,
+                    _ => return Err("--format needs text or json".to_string()),
+                };
+            }
+            "--jobs" => {
+                jobs = match args.next().map(|n| n.parse()) {
+                    Some(Ok(n)) if n > 0 => n,
+                    _ => return Err("--jobs needs a positive number".to_string()),
+                };
+            }
+            "--timeout" => {
+                let secs = args.next().and_then(|s| s.parse::<f64>().ok());
+                timeout = match secs {
+                    Some(secs) if secs > 0.0 && secs.is_finite() => {
+                        Some(Duration::from_secs_f64(secs))
+                    }
+                    _ => return Err("--timeout needs a positive number of seconds".to_string()),
+                };
+            }
+            "all" => command = Some(Command::All),
+            "verify" => command = Some(Command::Verify),
+            "debug" => command = Some(Command::Debug),
+            s => match s.parse::<usize>() {
+                Ok(day) => command = Some(Command::Day(day)),
+                Err(_) => return Err(format!("unknown argument {}", s)),
+            },
+        }
+    }
+
+    let command = command.ok_or("missing command")?;
+    let mut inputs = match inputs_dir {
+        Some(dir) => Inputs::new(dir),
+        None => Inputs::from_env(),
+    };
+    if let Some(source) = source {
+        if !matches!(command, Command::Day(_) | Command::Debug) {
+            return Err("--input can only be used with a single day or debug".to_string());
+        }
+        // The debugger prompt reads its commands from stdin
+        if matches!(command, Command::Debug) && matches!(source, Source::Stdin) {
+            return Err("debug can't read the program from stdin".to_string());
+        }
+        inputs = inputs.with_source(source);
+    }
+    if format == Format::Json && matches!(command, Command::Verify) {
+        return Err("--format json can't be used with verify".to_string());
+    }
+    let answers = answers.unwrap_or_else(|| inputs.dir().join("answers.toml"));
+
+    Ok(Options {
+        command,
+        inputs,
+        answers,
+        format,
+        jobs,
+        timeout,
+    })
+}
+
+fn run_one(day: usize, options: &Options) {
+    let solution = match solution::find(day) {
+        Some(solution) => solution,
+        None => {
+            eprintln!("unknown day {}", day);
+            process::exit(2);
+        }
+    };
+    if options.format == Format::Json || options.timeout.is_some() {
+        panic::set_hook(Box::new(|_| {}));
+        let results = runner::run_days(&[solution], &options.inputs, options.timeout);
+        match options.format {
+            Format::Json => print_json(&results),
+            Format::Text => {
+                for r in &results {
+                    match &r.answer {
+                        Ok(answer) => println!("{}:{}: {}", day, r.part, answer),
+                        Err(e) => eprintln!("{}:{}: {}", day, r.part, e),
+                    }
+                }
+            }
+        }
+        if !results.iter().all(PartResult::passed) {
+            process::exit(1);
+        }
+        return;
+    }
+    let input = match options.inputs.load(day) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
+    let result = solution.parse(&input).and_then(|parsed| {
+        for part in 1..=2 {
+            println!("{}:{}: {}", day, part, solution.solve(part, &*parsed)?);
+        }
+        Ok(())
+    });
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+/// The first line of an error, cut short so it fits in a table cell.
+fn short_error(e: &str) -> String {
+    const MAX_LEN: usize = 60;
+    let line = e.lines().next().unwrap_or_default();
+    if line.chars().count() > MAX_LEN {
+        format!("{}...", line.chars().take(MAX_LEN).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+fn print_table(results: &[PartResult]) {
+    let rows = results
+        .iter()
+        .map(|r| {
+            let (answer, status) = match &r.answer {
+                Ok(answer) => (answer.to_string(), "ok".to_string()),
+                Err(Failure::Timeout(_)) => ("-".to_string(), "TIMEOUT".to_string()),
+                Err(Failure::Error(e)) => ("-".to_string(), format!("FAIL ({})", short_error(e))),
+            };
+            [
+                r.day.to_string(),
+                r.part.to_string(),
+                answer,
+                runner::format_duration(r.duration),
+                status,
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    let header = ["Day", "Part", "Answer", "Time", "Status"];
+    let mut widths = header.map(str::len);
+    for row in &rows {
+        for (w, cell) in widths.iter_mut().zip(row.iter()) {
+            *w = (*w).max(cell.chars().count());
+        }
+    }
+
+    println!(
+        "{:>w0$}  {:>w1$}  {:<w2$}  {:>w3$}  {}",
+        header[0],
+        header[1],
+        header[2],
+        header[3],
+        header[4],
+        w0 = widths[0],
+        w1 = widths[1],
+        w2 = widths[2],
+        w3 = widths[3],
+    );
+    for row in &rows {
+        println!(
+            "{:>w0$}  {:>w1$}  {:<w2$}  {:>w3$}  {}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            row[4],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+            w3 = widths[3],
+        );
+    }
+
+    let total: Duration = results.iter().map(|r| r.duration).sum();
+    let passed = results.iter().filter(|r| r.passed()).count();
+    println!(
+        "Total: {} parts in {}, {} ok, {} failed",
+        results.len(),
+        runner::format_duration(total),
+        passed,
+        results.len() - passed
+    );
+}
+
+fn print_json(results: &[PartResult]) {
+    for r in results {
+        println!("{}", json::record(r));
+    }
+}
+
+fn run_all(options: &Options) {
+    // Failures are reported in the output, keep the default hook from
+    // printing every panic as well
+    panic::set_hook(Box::new(|_| {}));
+
+    let results = runner::run_days_parallel(
+        &solution::registry(),
+        &options.inputs,
+        options.jobs,
+        options.timeout,
+    );
+    match options.format {
+        Format::Text => print_table(&results),
+        Format::Json => print_json(&results),
+    }
+}
+
+/// Runs every day with a known answer and prints a diff for each part that
+/// doesn't match. Exits non-zero if anything differs.
+fn verify(options: &Options) {
+    let answers = match Answers::load(&options.answers) {
+        Ok(answers) => answers,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(2);
+        }
+    };
+    panic::set_hook(Box::new(|_| {}));
 
-    let day = match a.next() {
-        Some(s) => usize::from_str_radix(&s, 10).unwrap(),
-        None => 0,
+    let (days, unknown): (Vec<_>, Vec<_>) = answers
+        .days()
+        .into_iter()
+        .partition(|day| solution::find(*day).is_some());
+    let days = days
+        .into_iter()
+        .filter_map(solution::find)
+        .collect::<Vec<_>>();
+    let mut results =
+        runner::run_days_parallel(&days, &options.inputs, options.jobs, options.timeout);
+    for day in unknown {
+        for part in 1..=2 {
+            results.push(PartResult::failed(day, part, "unknown day".into()));
+        }
+    }
+    results.retain(|r| answers.get(r.day, r.part).is_some());
+    results.sort_by_key(|r| (r.day, r.part));
+
+    let mut checked = 0;
+    let mut mismatches = 0;
+    for r in &results {
+        let expected = answers.get(r.day, r.part).unwrap();
+        checked += 1;
+        let actual = match &r.answer {
+            Ok(answer) if answer == expected => continue,
+            Ok(answer) => answer.to_string(),
+            Err(Failure::Timeout(_)) => "TIMEOUT".to_string(),
+            Err(Failure::Error(e)) => format!("error: {}", short_error(e)),
+        };
+        mismatches += 1;
+        println!("day {}, part {}:", r.day, r.part);
+        println!("- {}", expected);
+        println!("+ {}", actual);
+    }
+
+    println!("{} answers checked, {} mismatched", checked, mismatches);
+    if mismatches > 0 {
+        process::exit(1);
+    }
+}
+
+/// The day whose input `debug` loads by default.
+const DEBUG_DAY: usize = 8;
+
+fn debug(options: &Options) {
+    let cpu = options
+        .inputs
+        .load(DEBUG_DAY)
+        .and_then(|program| CPU::new(&program));
+    let mut debugger = match cpu {
+        Ok(cpu) => Debugger::new(cpu),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    let mut last = String::new();
+    loop {
+        print!("(dbg) ");
+        io::stdout().flush().unwrap();
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            _ => {
+                println!();
+                break;
+            }
+        };
+        // An empty line repeats the last command
+        if !line.trim().is_empty() {
+            last = line;
+        }
+        match debugger.command(&last) {
+            Some(reply) => print!("{}", reply),
+            None => break,
+        }
+    }
+}
+
+fn main() {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
     };
 
-    match day {
-        1 => aoc1::run(),
-        2 => aoc2::run(),
-        3 => aoc3::run(),
-        4 => aoc4::run(),
-        5 => aoc5::run(),
-        6 => aoc6::run(),
-        7 => aoc7::run(),
-        8 => aoc8::run(),
-        9 => aoc9::run(),
-        10 => aoc10::run(),
-        11 => aoc11::run(),
-        12 => aoc12::run(),
-        13 => aoc13::run(),
-        14 => aoc14::run(),
-        15 => aoc15::run(),
-        16 => aoc16::run(),
-        17 => aoc17::run(),
-        18 => aoc18::run(),
-        19 => aoc19::run(),
-        20 => aoc20::run(),
-        21 => aoc21::run(),
-        22 => aoc22::run(),
-        23 => aoc23::run(),
-        24 => aoc24::run(),
-        25 => aoc25::run(),
-        _ => (),
+    match options.command {
+        Command::Day(day) => run_one(day, &options),
+        Command::All => run_all(&options),
+        Command::Verify => verify(&options),
+        Command::Debug => debug(&options),
     }
 }
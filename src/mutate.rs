@@ -0,0 +1,370 @@
+//! Searching for small edits that make a handheld console program do what
+//! it should. `search` tries programs with one edit, then two, and so on,
+//! so the edits it finds are as few as possible:
+//!
+//! ```
+//! use advent_of_code2019::intcode::{Op, Termination, CPU};
+//! use advent_of_code2019::mutate::{self, Config, Edit, FlipJmpNop, Outcome};
+//!
+//! let code = CPU::new("acc +1\njmp -1").unwrap().code;
+//! let halts = |t: &Termination, _: &CPU| matches!(t, Termination::Halted { .. });
+//! let outcome = mutate::search(&code, &[&FlipJmpNop], halts, &Config::default());
+//! let edit = Edit::Replace { pc: 1, op: Op::Nop(-1) };
+//! assert!(matches!(outcome, Outcome::Found { edits, .. } if edits == vec![edit]));
+//! ```
+
+use crate::intcode::{Op, Termination, CPU};
+use crate::threaded::Program;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// One change to a program. Jump offsets aren't adjusted for inserted or
+/// deleted instructions.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Edit {
+    Replace {
+        pc: usize,
+        op: Op,
+    },
+    Delete {
+        pc: usize,
+    },
+    /// Insert `op` before the instruction at `pc`, or at the end.
+    Insert {
+        pc: usize,
+        op: Op,
+    },
+}
+
+impl Edit {
+    pub fn apply(&self, code: &mut Vec<Op>) {
+        match *self {
+            Edit::Replace { pc, op } => code[pc] = op,
+            Edit::Delete { pc } => {
+                code.remove(pc);
+            }
+            Edit::Insert { pc, op } => code.insert(pc, op),
+        }
+    }
+}
+
+impl fmt::Display for Edit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Edit::Replace { pc, op } => write!(f, "replace pc {} with {}", pc, op),
+            Edit::Delete { pc } => write!(f, "delete pc {}", pc),
+            Edit::Insert { pc, op } => write!(f, "insert {} at pc {}", op, pc),
+        }
+    }
+}
+
+/// Generates the edits to try on a program.
+pub trait Mutator {
+    fn mutations(&self, code: &[Op]) -> Vec<Edit>;
+}
+
+impl<F: Fn(&[Op]) -> Vec<Edit>> Mutator for F {
+    fn mutations(&self, code: &[Op]) -> Vec<Edit> {
+        self(code)
+    }
+}
+
+/// Turns a `jmp` into a `nop` or the other way around.
+pub struct FlipJmpNop;
+
+impl Mutator for FlipJmpNop {
+    fn mutations(&self, code: &[Op]) -> Vec<Edit> {
+        code.iter()
+            .enumerate()
+            .filter_map(|(pc, op)| {
+                let op = match *op {
+                    Op::Jmp(v) => Op::Nop(v),
+                    Op::Nop(v) => Op::Jmp(v),
+                    _ => return None,
+                };
+                Some(Edit::Replace { pc, op })
+            })
+            .collect()
+    }
+}
+
+/// Changes the argument of an instruction by up to `max_delta` either way.
+pub struct TweakArgument {
+    pub max_delta: i64,
+}
+
+impl Mutator for TweakArgument {
+    fn mutations(&self, code: &[Op]) -> Vec<Edit> {
+        let deltas = (-self.max_delta..=self.max_delta).filter(|d| *d != 0);
+        let mut edits = Vec::new();
+        for (pc, op) in code.iter().enumerate() {
+            for delta in deltas.clone() {
+                let tweak = |v: i64| v.checked_add(delta);
+                let op = match *op {
+                    Op::Nop(v) => tweak(v).map(Op::Nop),
+                    Op::Acc(v) => tweak(v).map(Op::Acc),
+                    Op::Jmp(v) => tweak(v).map(Op::Jmp),
+                    _ => None,
+                };
+                edits.extend(op.map(|op| Edit::Replace { pc, op }));
+            }
+        }
+        edits
+    }
+}
+
+/// Removes an instruction.
+pub struct DeleteInstruction;
+
+impl Mutator for DeleteInstruction {
+    fn mutations(&self, code: &[Op]) -> Vec<Edit> {
+        (0..code.len()).map(|pc| Edit::Delete { pc }).collect()
+    }
+}
+
+/// Inserts any of `ops` anywhere.
+pub struct InsertInstruction {
+    pub ops: Vec<Op>,
+}
+
+impl Mutator for InsertInstruction {
+    fn mutations(&self, code: &[Op]) -> Vec<Edit> {
+        (0..=code.len())
+            .flat_map(|pc| self.ops.iter().map(move |op| Edit::Insert { pc, op: *op }))
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// The most edits to combine.
+    pub max_edits: usize,
+    /// The most programs to run, counting the unedited one.
+    pub budget: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            max_edits: 1,
+            budget: 100_000,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+    /// `edits`, applied in order, make the program meet the goal.
+    Found {
+        edits: Vec<Edit>,
+        termination: Termination,
+    },
+    /// No program within `max_edits` edits meets the goal.
+    Exhausted { tried: usize },
+    /// The budget ran out first.
+    OutOfBudget { tried: usize },
+}
+
+/// Runs `code` from the start, compiled if it is a handheld program.
+fn run(code: Vec<Op>) -> (Termination, CPU) {
+    let mut cpu = CPU::from_code(code);
+    let termination = match Program::compile(&cpu.code) {
+        Some(mut program) => program.run(&mut cpu),
+        None => cpu.run(),
+    };
+    (termination, cpu)
+}
+
+/// Runs `code`, which is `compiled` after `edit`. A replacement is patched
+/// into `compiled` for the run, anything else compiles `code` anew.
+fn run_edited(compiled: Option<&mut Program>, edit: &Edit, code: Vec<Op>) -> (Termination, CPU) {
+    if let (Some(program), Edit::Replace { pc, op }) = (compiled, edit) {
+        if let Some(old) = program.replace(*pc, *op) {
+            let mut cpu = CPU::from_code(code);
+            let termination = program.run(&mut cpu);
+            program.replace(*pc, old);
+            return (termination, cpu);
+        }
+    }
+    run(code)
+}
+
+/// A 128-bit digest of `code`, two hashes seeded apart, so that programs
+/// told apart by it practically never collide.
+fn digest(code: &[Op]) -> (u64, u64) {
+    let hash = |seed: u64| {
+        let mut hasher = DefaultHasher::new();
+        seed.hash(&mut hasher);
+        code.hash(&mut hasher);
+        hasher.finish()
+    };
+    (hash(0), hash(1))
+}
+
+/// Finds the fewest edits from `mutators` that make `code` run to a
+/// termination `goal` accepts. Programs that more than one sequence of
+/// edits leads to are only run once, told apart by a digest of their code.
+pub fn search<G>(code: &[Op], mutators: &[&dyn Mutator], goal: G, config: &Config) -> Outcome
+where
+    G: Fn(&Termination, &CPU) -> bool,
+{
+    let (termination, cpu) = run(code.to_vec());
+    if goal(&termination, &cpu) {
+        return Outcome::Found {
+            edits: Vec::new(),
+            termination,
+        };
+    }
+    let mut seen = HashSet::new();
+    seen.insert(digest(code));
+    let mut tried = 1;
+    // Edit sequences of the current length, applied to `code` again when
+    // extended rather than keeping every program they make
+    let mut frontier = vec![Vec::new()];
+
+    for depth in 1..=config.max_edits {
+        let mut next = Vec::new();
+        for edits in &frontier {
+            let mut program = code.to_vec();
+            edits.iter().for_each(|e: &Edit| e.apply(&mut program));
+            let mut compiled = Program::compile(&program);
+            for edit in mutators.iter().flat_map(|m| m.mutations(&program)) {
+                let mut candidate = program.clone();
+                edit.apply(&mut candidate);
+                if !seen.insert(digest(&candidate)) {
+                    continue;
+                }
+                if tried == config.budget {
+                    return Outcome::OutOfBudget { tried };
+                }
+                tried += 1;
+
+                let (termination, cpu) = run_edited(compiled.as_mut(), &edit, candidate);
+                let mut edits = edits.clone();
+                edits.push(edit);
+                if goal(&termination, &cpu) {
+                    return Outcome::Found { edits, termination };
+                }
+                if depth < config.max_edits {
+                    next.push(edits);
+                }
+            }
+        }
+        frontier = next;
+    }
+    Outcome::Exhausted { tried }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Config, DeleteInstruction, Edit, FlipJmpNop, InsertInstruction, Outcome, TweakArgument,
+    };
+    use crate::intcode::{Op, Termination, CPU, EXAMPLE};
+
+    fn halts(t: &Termination, _: &CPU) -> bool {
+        matches!(t, Termination::Halted { .. })
+    }
+
+    #[test]
+    fn mutate_flip() {
+        let code = CPU::new(EXAMPLE).unwrap().code;
+        let outcome = super::search(&code, &[&FlipJmpNop], halts, &Config::default());
+        assert_eq!(
+            outcome,
+            Outcome::Found {
+                edits: vec![Edit::Replace {
+                    pc: 7,
+                    op: Op::Nop(-4)
+                }],
+                termination: Termination::Halted { acc: 8 }
+            }
+        );
+    }
+
+    #[test]
+    fn mutate_minimal_edits() {
+        // Needs both jumps gone, and to end with acc 3
+        let code = CPU::new("jmp +0\nacc +3\njmp -1").unwrap().code;
+        let goal = |t: &Termination, _: &CPU| *t == Termination::Halted { acc: 3 };
+        let config = Config {
+            max_edits: 1,
+            ..Config::default()
+        };
+        let outcome = super::search(&code, &[&DeleteInstruction], goal, &config);
+        assert_eq!(outcome, Outcome::Exhausted { tried: 4 });
+
+        let config = Config {
+            max_edits: 2,
+            ..Config::default()
+        };
+        match super::search(&code, &[&FlipJmpNop, &DeleteInstruction], goal, &config) {
+            Outcome::Found { edits, .. } => {
+                assert_eq!(edits.len(), 2);
+                let mut patched = code.clone();
+                edits.iter().for_each(|e| e.apply(&mut patched));
+                let mut cpu = CPU::from_code(patched);
+                assert_eq!(cpu.run(), Termination::Halted { acc: 3 });
+            }
+            outcome => panic!("{:?}", outcome),
+        }
+
+        let config = Config {
+            max_edits: 2,
+            budget: 5,
+        };
+        let insert = InsertInstruction {
+            ops: vec![Op::Acc(1)],
+        };
+        let outcome = super::search(&code, &[&insert], goal, &config);
+        assert_eq!(outcome, Outcome::OutOfBudget { tried: 5 });
+
+        // Already fine
+        let goal = |t: &Termination, _: &CPU| matches!(t, Termination::InfiniteLoop { .. });
+        let outcome = super::search(&code, &[&insert], goal, &config);
+        assert!(matches!(outcome, Outcome::Found { edits, .. } if edits.is_empty()));
+    }
+
+    #[test]
+    fn mutate_custom_mutator() {
+        // Replace everything by a nop
+        let nops = |code: &[Op]| {
+            (0..code.len())
+                .map(|pc| Edit::Replace { pc, op: Op::Nop(0) })
+                .collect()
+        };
+        let code = CPU::new("acc +1\njmp -1").unwrap().code;
+        let outcome = super::search(&code, &[&nops], halts, &Config::default());
+        let edit = Edit::Replace {
+            pc: 1,
+            op: Op::Nop(0),
+        };
+        assert!(matches!(outcome, Outcome::Found { edits, .. } if edits == vec![edit]));
+
+        // acc +1 needs to become acc +3
+        let code = CPU::new("acc +1").unwrap().code;
+        let goal = |t: &Termination, _: &CPU| *t == Termination::Halted { acc: 3 };
+        let tweak = TweakArgument { max_delta: 2 };
+        let outcome = super::search(&code, &[&tweak], goal, &Config::default());
+        assert_eq!(
+            outcome,
+            Outcome::Found {
+                edits: vec![Edit::Replace {
+                    pc: 0,
+                    op: Op::Acc(3)
+                }],
+                termination: Termination::Halted { acc: 3 }
+            }
+        );
+        assert_eq!(
+            Edit::Insert {
+                pc: 2,
+                op: Op::Acc(-1)
+            }
+            .to_string(),
+            "insert acc -1 at pc 2"
+        );
+    }
+}
@@ -0,0 +1,340 @@
+use crate::input::Inputs;
+use crate::solution::{Answer, Day};
+use std::any::Any;
+use std::cell::RefCell;
+use std::fmt;
+use std::panic;
+use std::slice;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Why a part has no answer.
+#[derive(Debug, PartialEq)]
+pub enum Failure {
+    /// An error or a panic, with its message.
+    Error(String),
+    /// The day ran out of its time budget before this part was solved.
+    Timeout(Duration),
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Failure::Error(e) => write!(f, "{}", e),
+            Failure::Timeout(budget) => write!(f, "timed out after {}", format_duration(*budget)),
+        }
+    }
+}
+
+/// The outcome of solving one part of one day.
+#[derive(Debug)]
+pub struct PartResult {
+    pub day: usize,
+    pub part: usize,
+    pub answer: Result<Answer, Failure>,
+    pub duration: Duration,
+}
+
+impl PartResult {
+    pub fn failed(day: usize, part: usize, error: String) -> Self {
+        PartResult {
+            day,
+            part,
+            answer: Err(Failure::Error(error)),
+            duration: Duration::default(),
+        }
+    }
+
+    pub fn timed_out(day: usize, part: usize, budget: Duration) -> Self {
+        PartResult {
+            day,
+            part,
+            answer: Err(Failure::Timeout(budget)),
+            duration: budget,
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.answer.is_ok()
+    }
+}
+
+thread_local! {
+    static CANCEL: RefCell<Option<Arc<AtomicBool>>> = const { RefCell::new(None) };
+}
+
+/// What a cancelled solver unwinds with.
+struct Cancelled;
+
+/// Called from solver loops that may run for long. Unwinds out of the
+/// solver once the runner has given up on it, otherwise does nothing.
+pub fn checkpoint() {
+    let cancelled = CANCEL.with(|cancel| {
+        cancel
+            .borrow()
+            .as_ref()
+            .is_some_and(|c| c.load(Ordering::Relaxed))
+    });
+    if cancelled {
+        // Unlike panic!, this doesn't run the panic hook
+        panic::resume_unwind(Box::new(Cancelled));
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else if payload.is::<Cancelled>() {
+        "cancelled".to_string()
+    } else {
+        "panicked".to_string()
+    }
+}
+
+/// Parses `input` and solves `part`, timing both. Errors, and panics in the
+/// solver, are reported as a failed result.
+pub fn run_part(day: &Day, part: usize, input: &str) -> PartResult {
+    let start = Instant::now();
+    let answer = panic::catch_unwind(|| {
+        let parsed = day.parse(input)?;
+        day.solve(part, &*parsed)
+    })
+    .map_err(panic_message)
+    .and_then(|answer| answer.map_err(|e| e.to_string()))
+    .map_err(Failure::Error);
+    PartResult {
+        day: day.day,
+        part,
+        answer,
+        duration: start.elapsed(),
+    }
+}
+
+/// Solves both parts of `day`. With a `budget`, the day runs on its own
+/// thread and the parts not solved in time are reported as timed out. The
+/// solver is then cancelled at its next `checkpoint`, or left to finish in
+/// the background if it has none.
+pub fn run_day(day: &Day, input: &str, budget: Option<Duration>) -> Vec<PartResult> {
+    let budget = match budget {
+        Some(budget) => budget,
+        None => return (1..=2).map(|part| run_part(day, part, input)).collect(),
+    };
+
+    let (tx, rx) = mpsc::channel();
+    let cancel = Arc::new(AtomicBool::new(false));
+    let solver = {
+        let (day, input, cancel) = (*day, input.to_string(), cancel.clone());
+        move || {
+            CANCEL.with(|c| *c.borrow_mut() = Some(cancel.clone()));
+            for part in 1..=2 {
+                if cancel.load(Ordering::Relaxed) || tx.send(run_part(&day, part, &input)).is_err()
+                {
+                    return;
+                }
+            }
+        }
+    };
+    thread::Builder::new()
+        .name(format!("day {}", day.day))
+        .spawn(solver)
+        .expect("failed to spawn solver thread");
+
+    let deadline = Instant::now() + budget;
+    let mut timed_out = false;
+    (1..=2)
+        .map(|part| {
+            let left = deadline.saturating_duration_since(Instant::now());
+            match rx.recv_timeout(left) {
+                // Once a part has timed out, a late result is for that part
+                Ok(result) if !timed_out => result,
+                _ => {
+                    timed_out = true;
+                    cancel.store(true, Ordering::Relaxed);
+                    PartResult::timed_out(day.day, part, budget)
+                }
+            }
+        })
+        .collect()
+}
+
+/// Runs both parts of each of `days`, loading the inputs from `inputs`.
+/// A day whose input can't be loaded fails both parts.
+pub fn run_days(days: &[Day], inputs: &Inputs, budget: Option<Duration>) -> Vec<PartResult> {
+    let mut results = Vec::new();
+    for day in days {
+        match inputs.load(day.day) {
+            Ok(input) => results.extend(run_day(day, &input, budget)),
+            Err(e) => {
+                for part in 1..=2 {
+                    results.push(PartResult::failed(day.day, part, e.to_string()));
+                }
+            }
+        }
+    }
+    results
+}
+
+/// `run_days` on `threads` worker threads, each taking the next day not yet
+/// started. The results are still in the order of `days`.
+pub fn run_days_parallel(
+    days: &[Day],
+    inputs: &Inputs,
+    threads: usize,
+    budget: Option<Duration>,
+) -> Vec<PartResult> {
+    let next = AtomicUsize::new(0);
+    let mut results = thread::scope(|s| {
+        let workers = (0..threads.max(1))
+            .map(|_| {
+                s.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let idx = next.fetch_add(1, Ordering::Relaxed);
+                        match days.get(idx) {
+                            Some(day) => {
+                                results.push((idx, run_days(slice::from_ref(day), inputs, budget)))
+                            }
+                            None => return results,
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        // Panics are caught per part, so the workers themselves don't
+        workers
+            .into_iter()
+            .flat_map(|w| w.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+    results.sort_unstable_by_key(|(idx, _)| *idx);
+    results.into_iter().flat_map(|(_, r)| r).collect()
+}
+
+pub fn format_duration(d: Duration) -> String {
+    let micros = d.as_micros();
+    if micros < 1_000 {
+        format!("{}µs", micros)
+    } else if micros < 1_000_000 {
+        format!("{:.1}ms", micros as f64 / 1_000.0)
+    } else {
+        format!("{:.3}s", d.as_secs_f64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Result;
+    use crate::solution::{Answer, Day, Solution};
+    use std::time::Duration;
+
+    #[test]
+    fn runner_run_day() {
+        let day = crate::solution::find(1).unwrap();
+        let results = super::run_day(&day, "1721\n979\n366\n299\n675\n1456", None);
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.passed()));
+        assert_eq!(
+            results[1].answer,
+            Ok(crate::solution::Answer::Int(241861950))
+        );
+    }
+
+    /// A day whose second part panics.
+    struct Panics;
+
+    impl Solution for Panics {
+        const DAY: usize = 99;
+        type Input = ();
+
+        fn parse(_input: &str) -> Result<Self::Input> {
+            Ok(())
+        }
+
+        fn part1(_input: &Self::Input) -> Result<Answer> {
+            Ok(Answer::Int(1))
+        }
+
+        fn part2(_input: &Self::Input) -> Result<Answer> {
+            panic!("out of cheese")
+        }
+    }
+
+    #[test]
+    fn runner_catches_panic() {
+        let results = super::run_day(&Day::new::<Panics>(), "", None);
+        assert_eq!(results[0].answer, Ok(Answer::Int(1)));
+        assert_eq!(
+            results[1].answer,
+            Err(super::Failure::Error("out of cheese".to_string()))
+        );
+    }
+
+    #[test]
+    fn runner_reports_error() {
+        let day = crate::solution::find(1).unwrap();
+        let result = super::run_part(&day, 1, "1721\n979x");
+        assert_eq!(
+            result.answer,
+            Err(super::Failure::Error(
+                "day 1, line 2, column 4: cannot parse \"979x\"".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn runner_timeout() {
+        // Part 2 plays 30 million turns, far more than fits in the budget
+        let day = crate::solution::find(15).unwrap();
+        let budget = Duration::from_millis(200);
+        let start = std::time::Instant::now();
+        let results = super::run_day(&day, "0,3,6", Some(budget));
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert_eq!(results[0].answer, Ok(crate::solution::Answer::Int(436)));
+        assert_eq!(results[1].answer, Err(super::Failure::Timeout(budget)));
+        assert_eq!(
+            results[1].answer.as_ref().unwrap_err().to_string(),
+            "timed out after 200.0ms"
+        );
+    }
+
+    #[test]
+    fn runner_run_days_parallel() {
+        let dir = std::env::temp_dir().join(format!("aoc-parallel-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("day1.txt"), "1721\n979\n366\n299\n675\n1456").unwrap();
+        std::fs::write(dir.join("day23.txt"), "389125467").unwrap();
+
+        let registry = crate::solution::registry();
+        let days = [registry[22], registry[0], registry[1]];
+        let inputs = crate::input::Inputs::new(&dir);
+        let results = super::run_days_parallel(&days, &inputs, 3, None);
+        let order = results.iter().map(|r| (r.day, r.part)).collect::<Vec<_>>();
+        assert_eq!(
+            order,
+            vec![(23, 1), (23, 2), (1, 1), (1, 2), (2, 1), (2, 2)]
+        );
+        // Day 23 panics and day 2 has no input, neither affects day 1
+        assert!(!results[0].passed());
+        assert!(results[2].passed() && results[3].passed());
+        assert!(!results[4].passed());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn runner_format_duration() {
+        assert_eq!(super::format_duration(Duration::from_micros(12)), "12µs");
+        assert_eq!(
+            super::format_duration(Duration::from_micros(1_234)),
+            "1.2ms"
+        );
+        assert_eq!(
+            super::format_duration(Duration::from_millis(2_500)),
+            "2.500s"
+        );
+    }
+}
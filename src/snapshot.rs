@@ -0,0 +1,219 @@
+//! Saving the state of a `CPU` to a file and resuming from it later. The
+//! format is text, one field per line in a fixed order, followed by the
+//! program of a handheld CPU:
+//!
+//! ```text
+//! snapshot 1
+//! isa handheld
+//! pc 2
+//! acc 1
+//! relative_base 0
+//! input
+//! output
+//! memory
+//! code
+//! nop +0
+//! acc +1
+//! jmp +4
+//! ```
+//!
+//! `input` and `output` are space separated, `memory` comma separated like
+//! Intcode. The code is one instruction per line as `asm::assemble` reads
+//! it, which covers Intcode instructions a handheld CPU may hold too. The `1` is the version of the format, bumped whenever it
+//! changes in a way older snapshots can't be read with.
+
+use crate::error::{Error, Result};
+use crate::intcode::CPU;
+use std::fmt::Write;
+use std::fs;
+use std::iter::FromIterator;
+use std::path::Path;
+use std::str::FromStr;
+
+pub const VERSION: u32 = 1;
+
+fn join<'a>(values: impl Iterator<Item = &'a i64>, sep: &str) -> String {
+    values.map(|v| v.to_string()).collect::<Vec<_>>().join(sep)
+}
+
+/// `cpu` as a snapshot.
+pub fn to_string(cpu: &CPU) -> String {
+    let mut out = String::new();
+    writeln!(out, "snapshot {}", VERSION).unwrap();
+    let isa = if cpu.is_handheld() {
+        "handheld"
+    } else {
+        "intcode"
+    };
+    writeln!(out, "isa {}", isa).unwrap();
+    writeln!(out, "pc {}", cpu.pc).unwrap();
+    writeln!(out, "acc {}", cpu.acc).unwrap();
+    writeln!(out, "relative_base {}", cpu.relative_base).unwrap();
+    writeln!(out, "input {}", join(cpu.input.iter(), " ")).unwrap();
+    writeln!(out, "output {}", join(cpu.output.iter(), " ")).unwrap();
+    writeln!(out, "memory {}", join(cpu.memory.iter(), ",")).unwrap();
+    writeln!(out, "code").unwrap();
+    for op in &cpu.code {
+        writeln!(out, "{}", op).unwrap();
+    }
+    out
+}
+
+/// The lines of a snapshot, with one field each up to the code.
+struct Lines<'a>(Vec<&'a str>);
+
+impl Lines<'_> {
+    fn invalid(&self, idx: usize) -> Error {
+        Error::Snapshot {
+            line: idx + 1,
+            text: self.0.get(idx).unwrap_or(&"").to_string(),
+        }
+    }
+
+    /// The value of the field on line `idx`, which must be `key`.
+    fn field(&self, idx: usize, key: &str) -> Result<&str> {
+        let line = self.0.get(idx).ok_or_else(|| self.invalid(idx))?;
+        match line.strip_prefix(key) {
+            Some(value) if value.is_empty() || value.starts_with(' ') => Ok(value.trim()),
+            _ => Err(self.invalid(idx)),
+        }
+    }
+
+    fn number<T: FromStr>(&self, idx: usize, key: &str) -> Result<T> {
+        self.field(idx, key)?.parse().map_err(|_| self.invalid(idx))
+    }
+
+    /// A field holding values separated by `sep`.
+    fn list<T: FromStr, C: FromIterator<T>>(&self, idx: usize, key: &str, sep: char) -> Result<C> {
+        let value = self.field(idx, key)?;
+        value
+            .split(sep)
+            .filter(|_| !value.is_empty())
+            .map(|v| v.trim().parse().ok())
+            .collect::<Option<C>>()
+            .ok_or_else(|| self.invalid(idx))
+    }
+}
+
+/// Reads back a CPU saved with `to_string`.
+pub fn parse(text: &str) -> Result<CPU> {
+    let lines = Lines(text.lines().collect());
+    if lines.number::<u32>(0, "snapshot")? != VERSION {
+        return Err(lines.invalid(0));
+    }
+    let mut cpu = match lines.field(1, "isa")? {
+        "handheld" => CPU::from_code(Vec::new()),
+        "intcode" => CPU::from_memory(Vec::new()),
+        _ => return Err(lines.invalid(1)),
+    };
+    cpu.pc = lines.number(2, "pc")?;
+    cpu.acc = lines.number(3, "acc")?;
+    cpu.relative_base = lines.number(4, "relative_base")?;
+    cpu.input = lines.list(5, "input", ' ')?;
+    cpu.output = lines.list(6, "output", ' ')?;
+    cpu.memory = lines.list(7, "memory", ',')?;
+    lines.field(8, "code")?;
+
+    for (idx, line) in lines.0.iter().enumerate().skip(9) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let op = crate::asm::assemble(line)
+            .ok()
+            .filter(|code| code.len() == 1)
+            .ok_or_else(|| lines.invalid(idx))?;
+        cpu.code.extend(op);
+    }
+    Ok(cpu)
+}
+
+pub fn save(cpu: &CPU, path: &Path) -> Result<()> {
+    fs::write(path, to_string(cpu)).map_err(|source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+pub fn load(path: &Path) -> Result<CPU> {
+    let text = fs::read_to_string(path).map_err(|source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    parse(&text)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intcode::{Op, Param, Termination, CPU};
+
+    #[test]
+    fn snapshot_handheld() {
+        let mut cpu = CPU::new("nop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99").unwrap();
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+        let text = super::to_string(&cpu);
+        assert!(text.starts_with("snapshot 1\nisa handheld\npc 2\nacc 1\n"));
+        assert!(text.ends_with("\ncode\nnop +0\nacc +1\njmp +4\nacc +3\njmp -3\nacc -99\n"));
+
+        let mut resumed = super::parse(&text).unwrap();
+        assert_eq!(super::to_string(&resumed), text);
+        assert_eq!(resumed.run(), Termination::Halted { acc: 1 });
+        assert_eq!(cpu.run(), Termination::Halted { acc: 1 });
+    }
+
+    #[test]
+    fn snapshot_handheld_intcode_ops() {
+        let code = vec![
+            Op::Acc(1),
+            Op::Add(Param::Immediate(1), Param::Immediate(2), Param::Position(0)),
+            Op::Load(Param::Relative(-3)),
+            Op::End,
+            Op::Data(42),
+        ];
+        let cpu = CPU::from_code(code.clone());
+        let text = super::to_string(&cpu);
+        assert!(text.ends_with("\ncode\nacc +1\nadd 1, 2, [0]\nload [rb-3]\nend\ndata 42\n"));
+        let resumed = super::parse(&text).unwrap();
+        assert!(resumed.is_handheld());
+        assert_eq!(resumed.code, code);
+    }
+
+    #[test]
+    fn snapshot_intcode() {
+        // Echoes two inputs
+        let mut cpu = CPU::intcode("3,0,4,0,3,0,4,0,99").unwrap();
+        cpu.input.extend(&[7, -8]);
+        cpu.step().unwrap();
+        cpu.step().unwrap();
+
+        let path = std::env::temp_dir().join(format!("aoc-snapshot-{}", std::process::id()));
+        super::save(&cpu, &path).unwrap();
+        let mut resumed = super::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(resumed.memory, cpu.memory);
+        assert_eq!(resumed.input, vec![-8]);
+        assert_eq!(resumed.output, vec![7]);
+        assert_eq!(resumed.run(), Termination::Halted { acc: 0 });
+        assert_eq!(resumed.output, vec![7, -8]);
+    }
+
+    #[test]
+    fn snapshot_errors() {
+        let error = |text: &str| super::parse(text).unwrap_err().to_string();
+        assert_eq!(
+            error("snapshot 2\n"),
+            "snapshot, line 1: cannot parse \"snapshot 2\""
+        );
+        assert_eq!(
+            error("snapshot 1\nisa handheld\npc -1\n"),
+            "snapshot, line 3: cannot parse \"pc -1\""
+        );
+        assert_eq!(
+            error("snapshot 1\nisa handheld\npc 0\nacc 0\n"),
+            "snapshot, line 5: cannot parse \"\""
+        );
+        let text = "snapshot 1\nisa handheld\npc 0\nacc 0\nrelative_base 0\ninput\noutput\nmemory\ncode\nnop +0\nfoo +1\n";
+        assert_eq!(error(text), "snapshot, line 11: cannot parse \"foo +1\"");
+    }
+}
@@ -0,0 +1,163 @@
+use crate::error::Result;
+use std::any::Any;
+use std::fmt;
+
+/// The answer to one part of a puzzle.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Answer {
+    Int(i128),
+    Str(String),
+    /// The part has no answer, e.g. the second half of day 25.
+    None,
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Answer::Int(v) => write!(f, "{}", v),
+            Answer::Str(s) => write!(f, "{}", s),
+            Answer::None => write!(f, "-"),
+        }
+    }
+}
+
+macro_rules! answer_from_int {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(v: $t) -> Self {
+                    Answer::Int(v as i128)
+                }
+            }
+        )*
+    };
+}
+
+answer_from_int!(i64, isize, u64, u128, usize);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Str(s)
+    }
+}
+
+/// A solution to a single day: parse the input once, then solve both parts
+/// from the parsed representation.
+pub trait Solution {
+    const DAY: usize;
+    type Input: 'static;
+
+    fn parse(input: &str) -> Result<Self::Input>;
+    fn part1(input: &Self::Input) -> Result<Answer>;
+    fn part2(input: &Self::Input) -> Result<Answer>;
+}
+
+/// A type erased `Solution`, so that all days can be kept in one registry.
+#[derive(Clone, Copy)]
+pub struct Day {
+    pub day: usize,
+    parse: fn(&str) -> Result<Box<dyn Any>>,
+    part1: fn(&dyn Any) -> Result<Answer>,
+    part2: fn(&dyn Any) -> Result<Answer>,
+}
+
+fn parse_erased<S: Solution>(input: &str) -> Result<Box<dyn Any>> {
+    Ok(Box::new(S::parse(input)?))
+}
+
+fn part1_erased<S: Solution>(input: &dyn Any) -> Result<Answer> {
+    S::part1(input.downcast_ref().unwrap())
+}
+
+fn part2_erased<S: Solution>(input: &dyn Any) -> Result<Answer> {
+    S::part2(input.downcast_ref().unwrap())
+}
+
+impl Day {
+    pub fn new<S: Solution>() -> Self {
+        Day {
+            day: S::DAY,
+            parse: parse_erased::<S>,
+            part1: part1_erased::<S>,
+            part2: part2_erased::<S>,
+        }
+    }
+
+    pub fn parse(&self, input: &str) -> Result<Box<dyn Any>> {
+        (self.parse)(input)
+    }
+
+    /// Solves `part` (1 or 2) from an input returned by `Day::parse`.
+    pub fn solve(&self, part: usize, input: &dyn Any) -> Result<Answer> {
+        match part {
+            1 => (self.part1)(input),
+            2 => (self.part2)(input),
+            _ => panic!("day {} has no part {}", self.day, part),
+        }
+    }
+}
+
+macro_rules! registry {
+    ($($day:path),* $(,)?) => {
+        /// All solved days, in day order.
+        pub fn registry() -> Vec<Day> {
+            vec![$(Day::new::<$day>()),*]
+        }
+    };
+}
+
+registry!(
+    crate::aoc1::Day1,
+    crate::aoc2::Day2,
+    crate::aoc3::Day3,
+    crate::aoc4::Day4,
+    crate::aoc5::Day5,
+    crate::aoc6::Day6,
+    crate::aoc7::Day7,
+    crate::aoc8::Day8,
+    crate::aoc9::Day9,
+    crate::aoc10::Day10,
+    crate::aoc11::Day11,
+    crate::aoc12::Day12,
+    crate::aoc13::Day13,
+    crate::aoc14::Day14,
+    crate::aoc15::Day15,
+    crate::aoc16::Day16,
+    crate::aoc17::Day17,
+    crate::aoc18::Day18,
+    crate::aoc19::Day19,
+    crate::aoc20::Day20,
+    crate::aoc21::Day21,
+    crate::aoc22::Day22,
+    crate::aoc23::Day23,
+    crate::aoc24::Day24,
+    crate::aoc25::Day25,
+);
+
+/// Looks up a single day in the registry.
+pub fn find(day: usize) -> Option<Day> {
+    registry().into_iter().find(|d| d.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn solution_registry() {
+        let days = super::registry();
+        assert_eq!(days.len(), 25);
+        assert!(days.iter().zip(1..).all(|(d, day)| d.day == day));
+        assert!(super::find(26).is_none());
+    }
+
+    #[test]
+    fn solution_solve() {
+        let day = super::find(1).unwrap();
+        let input = day.parse("1721\n979\n366\n299\n675\n1456").unwrap();
+        assert_eq!(day.solve(1, &*input).unwrap(), super::Answer::Int(514579));
+        assert_eq!(
+            day.solve(2, &*input).unwrap(),
+            super::Answer::Int(241861950)
+        );
+        assert!(day.parse("1721\nabc").is_err());
+    }
+}
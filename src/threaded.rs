@@ -0,0 +1,228 @@
+//! A faster backend for handheld console programs. Every instruction is
+//! decoded once into what it adds to acc and where execution goes on, with
+//! jump targets already resolved and bad jumps already found. Running a
+//! program then only follows that table, instead of fetching and matching
+//! an `Op` each step. It behaves exactly like `CPU::run`, which the tests
+//! check on random programs. `mutate::search` runs its candidates on it,
+//! patching the instruction a candidate replaces rather than compiling
+//! each one anew.
+
+use crate::intcode::{Fault, FaultKind, Op, Termination, CPU};
+use std::convert::TryFrom;
+
+/// A decoded instruction.
+#[derive(Clone, Copy, Debug)]
+struct Instruction {
+    add: i64,
+    next: Result<usize, FaultKind>,
+}
+
+pub struct Program {
+    ops: Vec<Op>,
+    code: Vec<Instruction>,
+    /// For each instruction, the last run that executed it, so that runs
+    /// don't have to clear it first.
+    visited: Vec<u32>,
+    runs: u32,
+}
+
+impl Instruction {
+    /// Decodes `op` at `pc` in a program of `len` instructions, or returns
+    /// `None` if it is an Intcode instruction.
+    fn decode(pc: usize, op: Op, len: usize) -> Option<Self> {
+        let jump = |offset: i64| {
+            let target = i64::try_from(pc)
+                .ok()
+                .and_then(|pc| pc.checked_add(offset))
+                .ok_or(FaultKind::JumpOutOfRange)?;
+            match usize::try_from(target) {
+                Ok(target) if target <= len => Ok(target),
+                Ok(_) => Err(FaultKind::JumpOutOfRange),
+                Err(_) => Err(FaultKind::NegativeJump),
+            }
+        };
+        match op {
+            Op::Nop(_) => Some(Instruction {
+                add: 0,
+                next: Ok(pc + 1),
+            }),
+            Op::Acc(add) => Some(Instruction {
+                add,
+                next: Ok(pc + 1),
+            }),
+            Op::Jmp(offset) => Some(Instruction {
+                add: 0,
+                next: jump(offset),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl Program {
+    /// Compiles a handheld program, or returns `None` if it has any Intcode
+    /// instructions.
+    pub fn compile(ops: &[Op]) -> Option<Self> {
+        let code = ops
+            .iter()
+            .enumerate()
+            .map(|(pc, op)| Instruction::decode(pc, *op, ops.len()))
+            .collect::<Option<Vec<_>>>()?;
+        Some(Program {
+            ops: ops.to_vec(),
+            visited: vec![0; code.len()],
+            code,
+            runs: 0,
+        })
+    }
+
+    /// Replaces the instruction at `pc` with `op` and returns the one it
+    /// was, or returns `None` and leaves it if `op` is an Intcode
+    /// instruction.
+    ///
+    /// # Panics
+    ///
+    /// If `pc` is past the end of the program.
+    pub fn replace(&mut self, pc: usize, op: Op) -> Option<Op> {
+        self.code[pc] = Instruction::decode(pc, op, self.code.len())?;
+        Some(std::mem::replace(&mut self.ops[pc], op))
+    }
+
+    /// Like `CPU::run` on a handheld CPU with this program, starting from
+    /// its pc and acc and updating them.
+    pub fn run(&mut self, cpu: &mut CPU) -> Termination {
+        self.runs = self.runs.wrapping_add(1);
+        if self.runs == 0 {
+            self.visited.iter_mut().for_each(|v| *v = 0);
+            self.runs = 1;
+        }
+        let (mut pc, mut acc) = (cpu.pc, cpu.acc);
+        let termination = loop {
+            let instruction = match self.code.get(pc) {
+                Some(instruction) => instruction,
+                None => break Termination::Halted { acc },
+            };
+            if self.visited[pc] == self.runs {
+                break Termination::InfiniteLoop { pc, acc };
+            }
+            self.visited[pc] = self.runs;
+            let next = acc
+                .checked_add(instruction.add)
+                .ok_or(FaultKind::AccOverflow)
+                .and_then(|sum| instruction.next.map(|next| (sum, next)));
+            match next {
+                Ok((sum, next)) => {
+                    acc = sum;
+                    pc = next;
+                }
+                Err(kind) => {
+                    break Termination::Faulted(Fault {
+                        pc,
+                        op: self.ops[pc],
+                        kind,
+                    })
+                }
+            }
+        };
+        cpu.pc = pc;
+        cpu.acc = acc;
+        termination
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::helper::Rng;
+    use crate::intcode::{Op, Termination, CPU, EXAMPLE};
+
+    fn random_program(rng: &mut Rng) -> Vec<Op> {
+        let mut below = |n| rng.below(n) as i64;
+        let len = 1 + below(30);
+        (0..len)
+            .map(|_| {
+                // Mostly small arguments, sometimes huge ones to overflow acc
+                // or jump far out of the program
+                let arg = match below(10) {
+                    0 => i64::MAX - below(3),
+                    1 => i64::MIN + below(3),
+                    _ => below(2 * len as u64 + 5) - len - 2,
+                };
+                match below(3) {
+                    0 => Op::Nop(arg),
+                    1 => Op::Acc(arg),
+                    _ => Op::Jmp(arg),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn threaded_matches_step() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        // How often each kind of termination came up
+        let mut kinds = [0; 3];
+        for _ in 0..5_000 {
+            let code = random_program(&mut rng);
+            let mut program = super::Program::compile(&code).unwrap();
+            let mut cpu = CPU::from_code(code.clone());
+            cpu.pc = rng.below(code.len() as u64 + 1) as usize;
+            cpu.acc = rng.below(100) as i64 - 50;
+            let mut compiled = cpu.clone();
+
+            let expected = cpu.run();
+            let start = compiled.clone();
+            assert_eq!(program.run(&mut compiled), expected, "{:?}", code);
+            assert_eq!((compiled.pc, compiled.acc), (cpu.pc, cpu.acc));
+            // Nothing is left over from the last run
+            compiled = start;
+            assert_eq!(program.run(&mut compiled), expected, "{:?}", code);
+            kinds[match expected {
+                Termination::Halted { .. } => 0,
+                Termination::InfiniteLoop { .. } => 1,
+                _ => 2,
+            }] += 1;
+        }
+        assert!(kinds.iter().all(|n| *n > 100), "{:?}", kinds);
+    }
+
+    #[test]
+    fn threaded_replace() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..1_000 {
+            let mut code = random_program(&mut rng);
+            let mut program = super::Program::compile(&code).unwrap();
+            let pc = rng.below(code.len() as u64) as usize;
+            let op = random_program(&mut rng)[0];
+            let old = code[pc];
+            assert_eq!(program.replace(pc, op), Some(old));
+            code[pc] = op;
+
+            let mut cpu = CPU::from_code(code.clone());
+            let mut compiled = cpu.clone();
+            assert_eq!(program.run(&mut compiled), cpu.run(), "{:?}", code);
+            assert_eq!(program.replace(pc, old), Some(op));
+        }
+        let mut program = super::Program::compile(&[Op::Nop(0)]).unwrap();
+        assert_eq!(program.replace(0, Op::End), None);
+        assert_eq!(program.ops, vec![Op::Nop(0)]);
+    }
+
+    #[test]
+    fn threaded_compile() {
+        let code = CPU::new(EXAMPLE).unwrap().code;
+        let mut program = super::Program::compile(&code).unwrap();
+        let mut cpu = CPU::from_code(code);
+        assert_eq!(
+            program.run(&mut cpu),
+            Termination::InfiniteLoop { pc: 1, acc: 5 }
+        );
+        // The run count wrapping round clears the marks
+        program.runs = u32::MAX;
+        let mut cpu = CPU::from_code(program.ops.clone());
+        assert_eq!(
+            program.run(&mut cpu),
+            Termination::InfiniteLoop { pc: 1, acc: 5 }
+        );
+        assert!(super::Program::compile(&[Op::End]).is_none());
+    }
+}
@@ -0,0 +1,268 @@
+//! Opt-in recording of every instruction a `CPU` executes, for finding out
+//! where and why a program goes wrong:
+//!
+//! ```
+//! use advent_of_code2019::intcode::CPU;
+//! use advent_of_code2019::trace::Trace;
+//!
+//! let mut cpu = CPU::new("acc +1\njmp -1").unwrap();
+//! let (trace, _) = Trace::record(&mut cpu);
+//! assert_eq!(trace.hot_loops()[0].to_string(), "pc 0..=1, 1 iterations");
+//! ```
+
+use crate::intcode::{Op, Termination, CPU};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::Write;
+
+/// One executed instruction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Event {
+    pub pc: usize,
+    pub op: Op,
+    pub acc_before: i64,
+    pub acc_after: i64,
+    /// Where execution went on.
+    pub next_pc: usize,
+}
+
+/// A backward jump that was taken, and so the loop it closes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HotLoop {
+    /// Where the jump lands.
+    pub start: usize,
+    /// Where the jump is.
+    pub end: usize,
+    pub iterations: usize,
+}
+
+impl fmt::Display for HotLoop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "pc {}..={}, {} iterations",
+            self.start, self.end, self.iterations
+        )
+    }
+}
+
+/// The instruction a replayed CPU executed differently from the trace.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Divergence {
+    pub step: usize,
+    pub expected: Event,
+    /// `None` if the CPU stopped instead.
+    pub actual: Option<Event>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let show = |e: &Event| {
+            format!(
+                "pc {}: {} (acc {} -> {})",
+                e.pc, e.op, e.acc_before, e.acc_after
+            )
+        };
+        write!(f, "step {}: expected {}, ", self.step, show(&self.expected))?;
+        match &self.actual {
+            Some(actual) => write!(f, "got {}", show(actual)),
+            None => write!(f, "but the program stopped"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Trace {
+    pub events: Vec<Event>,
+}
+
+/// Quotes a CSV field if it needs it. Intcode operands contain commas.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+impl Trace {
+    /// Runs `cpu` like `CPU::run`, recording what it executes.
+    pub fn record(cpu: &mut CPU) -> (Self, Termination) {
+        let mut events = Vec::new();
+        let termination = cpu.run_with(|pc, op, acc_before, cpu| {
+            events.push(Event {
+                pc,
+                op,
+                acc_before,
+                acc_after: cpu.acc,
+                next_pc: cpu.pc,
+            })
+        });
+        (Trace { events }, termination)
+    }
+
+    /// One JSON object per line and event, with its index as `step`.
+    pub fn to_jsonl(&self) -> String {
+        let mut out = String::new();
+        for (step, e) in self.events.iter().enumerate() {
+            writeln!(
+                out,
+                r#"{{"step":{},"pc":{},"op":{},"acc_before":{},"acc_after":{},"next_pc":{}}}"#,
+                step,
+                e.pc,
+                crate::json::string(&e.op.to_string()),
+                e.acc_before,
+                e.acc_after,
+                e.next_pc
+            )
+            .unwrap();
+        }
+        out
+    }
+
+    /// The same fields as `to_jsonl`, with a header line.
+    pub fn to_csv(&self) -> String {
+        let mut out = "step,pc,op,acc_before,acc_after,next_pc\n".to_string();
+        for (step, e) in self.events.iter().enumerate() {
+            writeln!(
+                out,
+                "{},{},{},{},{},{}",
+                step,
+                e.pc,
+                csv_field(&e.op.to_string()),
+                e.acc_before,
+                e.acc_after,
+                e.next_pc
+            )
+            .unwrap();
+        }
+        out
+    }
+
+    /// How often the instruction at each pc ran.
+    pub fn hit_counts(&self) -> BTreeMap<usize, usize> {
+        let mut counts = BTreeMap::new();
+        for e in &self.events {
+            *counts.entry(e.pc).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Every backward jump taken, most taken first.
+    pub fn hot_loops(&self) -> Vec<HotLoop> {
+        let mut edges = BTreeMap::new();
+        for e in &self.events {
+            if e.next_pc <= e.pc {
+                *edges.entry((e.next_pc, e.pc)).or_insert(0) += 1;
+            }
+        }
+        let mut loops = edges
+            .into_iter()
+            .map(|((start, end), iterations)| HotLoop {
+                start,
+                end,
+                iterations,
+            })
+            .collect::<Vec<_>>();
+        loops.sort_by_key(|l| Reverse(l.iterations));
+        loops
+    }
+
+    /// The `top` most executed instructions and the hot loops, as text.
+    pub fn report(&self, top: usize) -> String {
+        let mut out = format!("{} instructions executed\n", self.events.len());
+        let ops = self
+            .events
+            .iter()
+            .map(|e| (e.pc, e.op))
+            .collect::<BTreeMap<_, _>>();
+        let mut counts = self.hit_counts().into_iter().collect::<Vec<_>>();
+        counts.sort_by_key(|(pc, hits)| (Reverse(*hits), *pc));
+        for (pc, hits) in counts.into_iter().take(top) {
+            writeln!(out, "{:>8}x  pc {}: {}", hits, pc, ops[&pc]).unwrap();
+        }
+        for l in self.hot_loops().into_iter().take(top) {
+            writeln!(out, "loop {}", l).unwrap();
+        }
+        out
+    }
+
+    /// Steps `cpu` through the first `steps` events, checking that it
+    /// executes the same as it did when recorded. Starting from the state
+    /// the trace was recorded from, this reproduces the state after them.
+    pub fn replay(&self, cpu: &mut CPU, steps: usize) -> Result<(), Box<Divergence>> {
+        for (step, expected) in self.events.iter().take(steps).enumerate() {
+            let (pc, acc_before) = (cpu.pc, cpu.acc);
+            let actual = match (cpu.fetch(), cpu.step()) {
+                (Some((op, _)), Ok(crate::intcode::Step::Executed)) => Some(Event {
+                    pc,
+                    op,
+                    acc_before,
+                    acc_after: cpu.acc,
+                    next_pc: cpu.pc,
+                }),
+                _ => None,
+            };
+            if actual.as_ref() != Some(expected) {
+                return Err(Box::new(Divergence {
+                    step,
+                    expected: expected.clone(),
+                    actual,
+                }));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intcode::{Termination, CPU, EXAMPLE};
+
+    #[test]
+    fn trace_record() {
+        let mut cpu = CPU::new(EXAMPLE).unwrap();
+        let (trace, termination) = super::Trace::record(&mut cpu);
+        assert_eq!(termination, Termination::InfiniteLoop { pc: 1, acc: 5 });
+        assert_eq!(trace.events.len(), 7);
+        assert_eq!(trace.hit_counts()[&1], 1);
+
+        let loops = trace.hot_loops();
+        assert_eq!(loops.len(), 2);
+        assert_eq!(loops[0].to_string(), "pc 1..=4, 1 iterations");
+        assert!(trace.report(3).contains("loop pc 3..=7, 1 iterations\n"));
+
+        let jsonl = trace.to_jsonl();
+        assert_eq!(
+            jsonl.lines().nth(2),
+            Some(r#"{"step":2,"pc":2,"op":"jmp +4","acc_before":1,"acc_after":1,"next_pc":6}"#)
+        );
+        let csv = trace.to_csv();
+        assert_eq!(csv.lines().count(), 8);
+        assert_eq!(csv.lines().nth(2), Some("1,1,acc +1,0,1,2"));
+    }
+
+    #[test]
+    fn trace_replay() {
+        let mut cpu = CPU::new(EXAMPLE).unwrap();
+        let (trace, _) = super::Trace::record(&mut cpu.clone());
+        trace.replay(&mut cpu, 5).unwrap();
+        assert_eq!((cpu.pc, cpu.acc), (3, 2));
+
+        let mut patched = CPU::new(&EXAMPLE.replace("acc +3", "acc +2")).unwrap();
+        let divergence = trace.replay(&mut patched, 7).unwrap_err();
+        assert_eq!(divergence.step, 5);
+        assert_eq!(
+            divergence.to_string(),
+            "step 5: expected pc 3: acc +3 (acc 2 -> 5), got pc 3: acc +2 (acc 2 -> 4)"
+        );
+
+        let mut cpu = CPU::intcode("1,0,0,0,99").unwrap();
+        let (trace, _) = super::Trace::record(&mut cpu);
+        assert_eq!(
+            trace.to_csv().lines().nth(1),
+            Some("0,0,\"add [0], [0], [0]\",0,0,4")
+        );
+    }
+}